@@ -1,9 +1,13 @@
-use anyhow::anyhow;
-use deppy::{Dep, ServiceCollectionBuilder, ServiceHandler};
+use deppy::{ServiceCollectionBuilder, ServiceHandler};
 use deppy_macros::Injectable;
+use nightfall::localization::Localizations;
+use nightfall::modal::Modal as _;
 use nightfall::services::AddTwilightServices;
-use nightfall::{CommandController, CommandHandler};
-use nightfall_macros::{command, command_controller};
+use nightfall::spec::CommandSpec;
+use nightfall::{CommandHandler, Context};
+use nightfall_macros::{
+    command, command_controller, message_command, modal, user_command, Choice, Modal,
+};
 use serde::Deserialize;
 use std::env;
 use std::error::Error;
@@ -11,54 +15,44 @@ use std::fs::File;
 use twilight_cache_inmemory::InMemoryCache;
 use twilight_gateway::{Event, Shard};
 use twilight_http::Client as HttpClient;
+use twilight_model::application::command::{CommandOptionChoice, CommandOptionChoiceValue};
+use twilight_model::application::interaction::InteractionType;
+use twilight_model::channel::Message;
 use twilight_model::gateway::payload::incoming::InteractionCreate;
 use twilight_model::gateway::{Intents, ShardId};
-use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
-use twilight_model::id::marker::UserMarker;
-use twilight_model::id::Id;
-use twilight_util::builder::InteractionResponseDataBuilder;
+use twilight_model::http::interaction::InteractionResponse;
+use twilight_model::user::User;
 
 #[derive(Injectable)]
-struct Test {
-    client: Dep<HttpClient>,
-    cache: Dep<InMemoryCache>,
-}
+struct Test {}
 
 #[command_controller]
 impl Test {
     #[command(
         description = "User command, it's funny",
-        option(name = "user", description = "The user you wanna funny to")
+        localization = "user",
+        option(
+            name = "user",
+            description = "The user you wanna funny to",
+            localization = "user.target"
+        )
     )]
-    async fn user(
-        &self,
-        interaction: &InteractionCreate,
-        user_id: Id<UserMarker>,
-    ) -> Result<(), Box<dyn Error>> {
-        let user = self
-            .cache
-            .user(user_id)
-            .ok_or(anyhow!("Couldn't find user"))?;
-
-        let data = InteractionResponseDataBuilder::new()
-            .content(format!("User {} is super funny today", user.name))
-            .build();
-
-        let response: InteractionResponse = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(data),
-        };
+    async fn user(&self, user: User) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(format!("User {} is super funny today", user.name))
+    }
 
-        self.client
-            .interaction(Id::new(813708786493161523))
-            .create_response(interaction.id, &interaction.token, &response)
-            .await?;
+    #[user_command(name = "Show profile")]
+    async fn show_profile(&self, user: User) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(format!("This is the profile of {}", user.name))
+    }
 
-        Ok(())
+    #[message_command(name = "Bookmark")]
+    async fn bookmark(&self, message: Message) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(format!("Bookmarked a message from {}", message.author.name))
     }
 
     #[command(description = "User command, it's funny")]
-    async fn member(&self) -> Result<(), Box<dyn Error>> {
+    async fn member(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
         Ok(())
     }
 
@@ -71,71 +65,126 @@ impl Test {
             choice(name = "World", value = "Heaven")
         )
     )]
-    async fn echo(
+    async fn echo(&self, message: String) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(message)
+    }
+
+    /// Guesses a coin flip
+    #[command]
+    async fn guess(
         &self,
-        interaction: &InteractionCreate,
-        message: String,
-    ) -> Result<(), Box<dyn Error>> {
-        let data = InteractionResponseDataBuilder::new()
-            .content(message)
-            .build();
-
-        let response: InteractionResponse = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(data),
+        /// The side you think it lands on
+        side: CoinSide,
+    ) -> Result<&'static str, Box<dyn Error + Send + Sync>> {
+        let content = match side {
+            CoinSide::Heads => "It landed on heads, nice guess!",
+            CoinSide::Tails => "It landed on heads, better luck next time",
         };
 
-        self.client
-            .interaction(Id::new(813708786493161523))
-            .create_response(interaction.id, &interaction.token, &response)
-            .await?;
-        Ok(())
+        Ok(content)
     }
 }
 
-#[derive(Injectable)]
-struct TestSub {
-    client: Dep<HttpClient>,
+#[derive(Choice)]
+enum CoinSide {
+    #[choice(localization = "coin.heads")]
+    Heads,
+    #[choice(localization = "coin.tails")]
+    Tails,
 }
 
-#[command_controller(sub = "paru", sub_description = "Emulates paru")]
+#[derive(Injectable)]
+struct TestSub {}
+
+#[command_controller(sub = "paru", sub_description = "Emulates paru", dm_permission = false)]
 impl TestSub {
     #[command(
         name = "install",
         description = "Emulate installing a package",
-        option(name = "name", description = "The package name to install")
+        option(
+            name = "name",
+            description = "The package name to install",
+            autocomplete = "complete_package"
+        )
     )]
-    async fn install(
-        &self,
-        interaction: &InteractionCreate,
-        name: String,
-    ) -> Result<(), Box<dyn Error>> {
-        let data = InteractionResponseDataBuilder::new()
-            .content(format!("Installing package {}...", name))
-            .build();
-
-        let response_msg: InteractionResponse = InteractionResponse {
-            kind: InteractionResponseType::ChannelMessageWithSource,
-            data: Some(data),
-        };
-
-        self
-            .client
-            .interaction(Id::new(813708786493161523))
-            .create_response(interaction.id, &interaction.token, &response_msg)
-            .await?;
+    async fn install(ctx: Context, name: String) -> Result<(), nightfall::Error> {
+        ctx.reply(format!("Installing package {}...", name)).await?;
 
         let sleep_time = tokio::time::Duration::from_secs(5);
         tokio::time::sleep(sleep_time).await;
 
-        self.client
-            .interaction(Id::new(813708786493161523))
-            .create_followup(&interaction.token)
-            .content(&format!("Installed package {}!", name))?
-            .await?;
+        ctx.followup(format!("Installed package {}!", name)).await?;
 
         Ok(())
     }
+
+    async fn complete_package(
+        &self,
+        name: String,
+    ) -> Result<Vec<CommandOptionChoice>, Box<dyn Error + Send + Sync>> {
+        let choices = ["paru", "pacman", "yay"]
+            .into_iter()
+            .filter(|p| p.starts_with(&name))
+            .map(|p| CommandOptionChoice {
+                name: p.to_string(),
+                name_localizations: None,
+                value: CommandOptionChoiceValue::String(p.to_string()),
+            })
+            .collect();
+
+        Ok(choices)
+    }
+}
+
+#[derive(Injectable)]
+struct TestSubQuery {}
+
+#[command_controller(sub = "paru", sub_description = "Emulates paru", dm_permission = false)]
+impl TestSubQuery {
+    #[command(
+        name = "search",
+        description = "Emulate searching for a package",
+        option(name = "query", description = "What to search for")
+    )]
+    async fn search(&self, query: String) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(format!("No packages found for {}", query))
+    }
+}
+
+#[derive(Modal)]
+#[form(title = "Send feedback")]
+struct Feedback {
+    #[text_input(label = "What do you think?", style = "paragraph", max_length = 1000)]
+    message: String,
+    #[text_input(label = "Rating from 1 to 5", max_length = 1)]
+    rating: Option<u8>,
+}
+
+#[derive(Injectable)]
+struct FeedbackController {}
+
+#[command_controller(ephemeral)]
+impl FeedbackController {
+    #[command(description = "Send feedback about the bot")]
+    async fn feedback(&self) -> Result<InteractionResponse, Box<dyn Error + Send + Sync>> {
+        Ok(Feedback::create_modal("feedback"))
+    }
+
+    #[modal("feedback")]
+    async fn submit_feedback(
+        &self,
+        feedback: Feedback,
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let rating = feedback
+            .rating
+            .map(|r| format!(" and rated it {}/5", r))
+            .unwrap_or_default();
+
+        Ok(format!(
+            "Thanks for saying \"{}\"{}",
+            feedback.message, rating
+        ))
+    }
 }
 
 #[derive(Deserialize)]
@@ -156,11 +205,28 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
         .add_in_memory_cache()
         .add_scoped::<Test>()
         .add_scoped::<TestSub>()
+        .add_scoped::<TestSubQuery>()
+        .add_scoped::<FeedbackController>()
         .build();
 
+    let localizations = Localizations::new().add_locale_file("de", "./locales/de.lang")?;
+
     let command_handler = CommandHandler::new()
         .add_command::<Test>()
-        .add_command::<TestSub>();
+        .add_command::<TestSub>()
+        .add_command::<TestSubQuery>()
+        .add_command::<FeedbackController>()
+        .command(
+            CommandSpec::new("ping", "Checks if the bot is alive"),
+            |_| async move { Ok::<_, nightfall::Error>("Pong!") },
+        )
+        .localizations(localizations)
+        .responder(collection.get_required_service::<HttpClient>())
+        .error_reply(|_| "Uh oh, something happened...");
+
+    for missing in command_handler.missing_localizations() {
+        println!("Missing translation {} for {}", missing.key, missing.locale);
+    }
 
     let mut shard = Shard::new(ShardId::ONE, config.token.clone(), Intents::GUILDS);
 
@@ -172,9 +238,7 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     };
 
     {
-        let mut commands = Test::build_commands();
-        let mut sub_commands = TestSub::build_commands();
-        commands.append(&mut sub_commands);
+        let commands = command_handler.build_commands()?;
 
         let client = collection.get_required_service::<HttpClient>();
         client
@@ -192,27 +256,35 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
             continue;
         };
 
-        let err = command_handler
-            .handle_command_interaction(&interacton, &collection)
-            .await;
-        if err.is_err() {
-            let client = collection.get_required_service::<HttpClient>();
-
-            println!("Error: {:#?}", err);
-
-            let response_data = InteractionResponseDataBuilder::new()
-                .content("Uh oh, something happened...")
-                .build();
-
-            let response: InteractionResponse = InteractionResponse {
-                kind: InteractionResponseType::ChannelMessageWithSource,
-                data: Some(response_data),
-            };
+        if interacton.kind == InteractionType::ApplicationCommandAutocomplete {
+            if let Err(e) = command_handler
+                .handle_autocomplete_interaction(&interacton, &collection)
+                .await
+            {
+                println!("Error: {:#?}", e);
+            }
+            continue;
+        }
 
-            client
-                .interaction(application_id)
-                .create_response(interacton.id, &interacton.token, &response)
-                .await?;
+        let result = match interacton.kind {
+            InteractionType::MessageComponent => {
+                command_handler
+                    .handle_component_interaction(&interacton, &collection)
+                    .await
+            }
+            InteractionType::ModalSubmit => {
+                command_handler
+                    .handle_modal_interaction(&interacton, &collection)
+                    .await
+            }
+            _ => {
+                command_handler
+                    .handle_command_interaction(&interacton, &collection)
+                    .await
+            }
+        };
+        if let Err(e) = result {
+            println!("Error: {:#?}", e);
         }
     }
 }
@@ -0,0 +1,134 @@
+use crate::validate::check_choice;
+use darling::{FromDeriveInput, FromVariant};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+
+#[derive(Debug, FromVariant)]
+#[darling(attributes(choice))]
+struct VariantInfo {
+    ident: syn::Ident,
+    name: Option<String>,
+    value: Option<syn::Lit>,
+    localization: Option<String>,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(supports(enum_unit))]
+struct ChoiceEnumInfo {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<VariantInfo, ()>,
+}
+
+pub(crate) fn generate_choice(
+    input: syn::DeriveInput,
+) -> Result<TokenStream, Box<dyn crate::Error>> {
+    let info = match ChoiceEnumInfo::from_derive_input(&input) {
+        Ok(i) => i,
+        Err(e) => return Err(Box::new(e)),
+    };
+
+    let variants = info.data.take_enum().unwrap();
+    if variants.len() > 25 {
+        return Err(Box::new(syn::Error::new(
+            input.span(),
+            "An option can not have more than 25 choices",
+        )));
+    }
+
+    // The choices are integer backed as soon as one of the variants has an integer value
+    let is_integer = variants
+        .iter()
+        .any(|v| matches!(v.value, Some(syn::Lit::Int(_))));
+
+    let mut choices = vec![];
+    let mut arms = vec![];
+    let mut localizations = vec![];
+    for variant in &variants {
+        let ident = &variant.ident;
+        let name = variant.name.clone().unwrap_or_else(|| ident.to_string());
+        check_choice(&name, variant.value.as_ref(), ident.span())?;
+
+        let value = match (&variant.value, is_integer) {
+            (Some(syn::Lit::Int(i)), true) => quote! { #i },
+            (Some(syn::Lit::Str(s)), false) => quote! { #s },
+            (None, false) => {
+                let value = ident.to_string();
+                quote! { #value }
+            }
+            (Some(v), _) => {
+                return Err(Box::new(syn::Error::new(
+                    v.span(),
+                    "Every choice value has to be either a string or an integer",
+                )))
+            }
+            (None, true) => {
+                return Err(Box::new(syn::Error::new(
+                    ident.span(),
+                    "Every variant needs an integer value if one of them has one",
+                )))
+            }
+        };
+
+        choices.push(quote! { (#name.to_string(), #value.into()) });
+        arms.push(quote! { #value => Some(Self::#ident) });
+        if let Some(key) = &variant.localization {
+            localizations.push(quote! { (#name, #key) });
+        }
+    }
+
+    let (backing, option_value, matched) = if is_integer {
+        (quote! { i64 }, quote! { Integer }, quote! { v })
+    } else {
+        (
+            quote! { ::std::string::String },
+            quote! { String },
+            quote! { v.as_str() },
+        )
+    };
+
+    let ident = &info.ident;
+    let (impl_generics, ty_generics, where_clause) = info.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::nightfall::register::CreateOption for #ident #ty_generics #where_clause {
+            fn create_option(
+                name: &str,
+                description: &str,
+                required: bool,
+                _: Vec<(String, Self)>,
+                constraints: &::nightfall::register::OptionConstraints,
+            ) -> ::nightfall::export::twilight_model::application::command::CommandOption {
+                <#backing as ::nightfall::register::CreateOption>::create_option(
+                    name,
+                    description,
+                    required,
+                    vec![#(#choices),*],
+                    constraints,
+                )
+            }
+
+            fn choice_localizations() -> &'static [(&'static str, &'static str)] {
+                &[#(#localizations),*]
+            }
+        }
+
+        impl #impl_generics ::nightfall::FromOption for #ident #ty_generics #where_clause {
+            fn from_option(
+                value: ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue,
+                _: &::nightfall::export::twilight_model::application::interaction::application_command::CommandData,
+            ) -> Option<Self> {
+                let ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::#option_value(v)
+                    = value else {
+                    return None;
+                };
+
+                match #matched {
+                    #(#arms,)*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
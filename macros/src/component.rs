@@ -0,0 +1,211 @@
+use crate::generate::{
+    check_receiver, generate_context, generate_extraction, generate_injection, is_context_param,
+    is_extract_param, is_inject_param,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::ops::Deref;
+use syn::spanned::Spanned;
+use syn::{ImplItemFn, LitStr};
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum PatternKind {
+    Component,
+    Modal,
+}
+
+pub(crate) fn get_pattern(fn_item: &ImplItemFn) -> Option<(PatternKind, syn::Result<LitStr>)> {
+    fn_item.attrs.iter().find_map(|a| {
+        let kind = if a.path().is_ident("component") {
+            PatternKind::Component
+        } else if a.path().is_ident("modal") {
+            PatternKind::Modal
+        } else {
+            return None;
+        };
+
+        Some((kind, a.parse_args::<LitStr>()))
+    })
+}
+
+const RESERVED_PARAMS: &[&str] = &["interaction", "data"];
+
+fn get_placeholders(pattern: &LitStr) -> syn::Result<Vec<String>> {
+    let value = pattern.value();
+
+    let mut placeholders = vec![];
+    let mut current: Option<String> = None;
+    let mut previous_was_placeholder = false;
+    for c in value.chars() {
+        match (c, current.as_mut()) {
+            ('{', None) => {
+                if previous_was_placeholder {
+                    return Err(syn::Error::new(
+                        pattern.span(),
+                        "Placeholders need to be separated by at least one character",
+                    ));
+                }
+                current = Some(String::new());
+            }
+            ('}', Some(name)) => {
+                if syn::parse_str::<syn::Ident>(name).is_err() {
+                    return Err(syn::Error::new(
+                        pattern.span(),
+                        format!("`{}` is not a valid placeholder name", name),
+                    ));
+                }
+                // These parameter names already receive the interaction and its data
+                if RESERVED_PARAMS.contains(&name.as_str()) {
+                    return Err(syn::Error::new(
+                        pattern.span(),
+                        format!("`{}` is reserved and can't be a placeholder name", name),
+                    ));
+                }
+                placeholders.push(name.clone());
+                current = None;
+                previous_was_placeholder = true;
+                continue;
+            }
+            ('{', Some(_)) | ('}', None) => {
+                return Err(syn::Error::new(
+                    pattern.span(),
+                    "Unbalanced braces in pattern",
+                ));
+            }
+            (c, Some(name)) => name.push(c),
+            (_, None) => {}
+        }
+        previous_was_placeholder = false;
+    }
+
+    if current.is_some() {
+        return Err(syn::Error::new(
+            pattern.span(),
+            "Unbalanced braces in pattern",
+        ));
+    }
+
+    Ok(placeholders)
+}
+
+pub(crate) fn generate_pattern_statement(
+    fn_item: &ImplItemFn,
+    kind: PatternKind,
+    pattern: &LitStr,
+) -> Result<TokenStream, Box<dyn crate::Error>> {
+    let ident = &fn_item.sig.ident;
+    let binding_failed = match kind {
+        PatternKind::Component => quote! { ::nightfall::Error::ComponentBindingFailed },
+        PatternKind::Modal => quote! { ::nightfall::Error::ModalBindingFailed },
+    };
+    let placeholders = match get_placeholders(pattern) {
+        Ok(p) => p,
+        Err(e) => return Err(Box::new(e)),
+    };
+
+    let mut args: Vec<TokenStream> = vec![];
+    let mut is_self = false;
+    for arg in fn_item.sig.inputs.iter() {
+        let ty = match arg {
+            syn::FnArg::Typed(t) => t,
+            syn::FnArg::Receiver(r) => {
+                check_receiver(r)?;
+                is_self = true;
+                continue;
+            }
+        };
+
+        let syn::Pat::Ident(i) = ty.pat.deref() else {
+            return Err(Box::new(syn::Error::new(
+                ty.span(),
+                "Unrecognised identifier",
+            )));
+        };
+
+        if i.ident == "interaction" {
+            args.push(quote! { interaction });
+        } else if i.ident == "data" {
+            args.push(quote! { data });
+        } else if is_extract_param(ty) {
+            args.push(generate_extraction(&ty.ty));
+        } else if is_inject_param(ty) {
+            args.push(generate_injection(&ty.ty));
+        } else if is_context_param(ty) {
+            args.push(generate_context());
+        } else if let Some(index) = placeholders.iter().position(|p| i.ident == p) {
+            args.push(quote! {
+                match segments[#index].parse() {
+                    Ok(v) => v,
+                    Err(_) => return Err(#binding_failed),
+                }
+            });
+        } else if kind == PatternKind::Modal {
+            // Whatever is left over is the form the modal gets parsed into
+            args.push(quote! {
+                match ::nightfall::modal::Modal::parse_modal(data) {
+                    Some(v) => v,
+                    None => return Err(#binding_failed),
+                }
+            });
+        } else {
+            return Err(Box::new(syn::Error::new(
+                i.span(),
+                format!("The pattern has no placeholder named `{}`", i.ident),
+            )));
+        }
+    }
+
+    let call = if is_self {
+        quote! { self.#ident(#(#args),*).await }
+    } else {
+        quote! { Self::#ident(#(#args),*).await }
+    };
+
+    let segments = if placeholders.is_empty() {
+        quote! { _ }
+    } else {
+        quote! { segments }
+    };
+
+    Ok(quote! {
+        if let Some(#segments) = ::nightfall::component::match_custom_id(#pattern, &data.custom_id) {
+            return match #call {
+                Ok(r) => Ok(::nightfall::response::IntoResponse::into_response(r)),
+                Err(e) => Err(::std::convert::Into::<::nightfall::Error>::into(e)),
+            };
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+
+    fn placeholders(pattern: &str) -> syn::Result<Vec<String>> {
+        get_placeholders(&LitStr::new(pattern, Span::call_site()))
+    }
+
+    #[test]
+    fn finds_placeholders() {
+        assert_eq!(
+            placeholders("ticket:{id}:{action}").unwrap(),
+            ["id", "action"]
+        );
+        assert!(placeholders("close").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(placeholders("page:{page").is_err());
+        assert!(placeholders("page}").is_err());
+        assert!(placeholders("{a}{b}").is_err());
+        assert!(placeholders("page:{1st}").is_err());
+    }
+
+    #[test]
+    fn rejects_reserved_placeholders() {
+        assert!(placeholders("page:{data}").is_err());
+        assert!(placeholders("page:{interaction}").is_err());
+    }
+}
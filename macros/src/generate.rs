@@ -1,20 +1,26 @@
-﻿use darling::FromAttributes;
+use crate::component::{generate_pattern_statement, get_pattern, PatternKind};
+use crate::validate::{
+    check_command_length, check_context_menu_name, check_description, check_name, check_option,
+    check_options, check_sub_commands, option_length, resolve_description, value_span,
+};
+use darling::FromAttributes;
 use proc_macro2::TokenStream;
 use quote::quote;
+use std::collections::HashMap;
 use std::ops::Deref;
 use syn::spanned::Spanned;
 use syn::{ImplItem, ImplItemFn, ItemImpl};
 
-fn get_inner_optional(ty: &syn::Type) -> Option<syn::Type> {
+pub(crate) fn get_inner_optional(ty: &syn::Type) -> Option<syn::Type> {
     let syn::Type::Path(path) = ty else {
         return None;
     };
 
-    if !path.path.is_ident("Option") {
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
         return None;
     };
 
-    let segment = path.path.segments.first().unwrap();
     let syn::PathArguments::AngleBracketed(bracket) = &segment.arguments else {
         return None;
     };
@@ -28,42 +34,541 @@ fn get_inner_optional(ty: &syn::Type) -> Option<syn::Type> {
     })
 }
 
-fn get_arg(
-    args: &syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
-    index: usize,
-    offset: &mut usize,
-    interaction_name: &String,
-) -> Option<syn::FnArg> {
-    let arg = match args.get(index + *offset) {
-        Some(a) => a,
-        None => return None,
+fn get_context_menu_kind(fn_item: &ImplItemFn) -> Option<TokenStream> {
+    fn_item.attrs.iter().find_map(|a| {
+        if a.path().is_ident("user_command") {
+            Some(quote! { User })
+        } else if a.path().is_ident("message_command") {
+            Some(quote! { Message })
+        } else {
+            None
+        }
+    })
+}
+
+pub(crate) fn check_receiver(r: &syn::Receiver) -> Result<(), Box<dyn crate::Error>> {
+    if r.mutability.is_some() {
+        return Err(Box::new(syn::Error::new(
+            r.span(),
+            "Self must be non mutable reference", // Dependency injection only gives us a non mutable arc
+        )));
+    } else if r.reference.is_none() {
+        return Err(Box::new(syn::Error::new(
+            r.span(),
+            "Self must be reference", // We can not move it cause it is a part of a Arc that is managed by the dependency injection
+        )));
+    }
+
+    Ok(())
+}
+
+fn generate_context_menu_call(
+    fn_item: &ImplItemFn,
+    interaction_param: &str,
+) -> Result<TokenStream, Box<dyn crate::Error>> {
+    let ident = &fn_item.sig.ident;
+
+    let mut args: Vec<TokenStream> = vec![];
+    let mut is_self = false;
+    for arg in fn_item.sig.inputs.iter() {
+        let ty = match arg {
+            syn::FnArg::Typed(t) => t,
+            syn::FnArg::Receiver(r) => {
+                check_receiver(r)?;
+                is_self = true;
+                continue;
+            }
+        };
+
+        let syn::Pat::Ident(i) = ty.pat.deref() else {
+            return Err(Box::new(syn::Error::new(
+                ty.span(),
+                "Unrecognised identifier",
+            )));
+        };
+
+        if i.ident == interaction_param {
+            args.push(quote! { interaction })
+        } else if is_extract_param(ty) {
+            args.push(generate_extraction(&ty.ty));
+        } else if is_inject_param(ty) {
+            args.push(generate_injection(&ty.ty));
+        } else if is_context_param(ty) {
+            args.push(generate_context());
+        } else {
+            args.push(quote! {
+                match ::nightfall::FromTarget::from_target(data) {
+                    Some(v) => v,
+                    None => {
+                        return Err(::nightfall::Error::TargetBindingFailed {
+                            command: data.name.clone(),
+                        })
+                    }
+                }
+            });
+        }
+    }
+
+    Ok(if is_self {
+        quote! { self.#ident(#(#args),*).await }
+    } else {
+        quote! { Self::#ident(#(#args),*).await }
+    })
+}
+
+fn get_autocomplete_handlers(
+    fn_items: &[&ImplItemFn],
+) -> Result<Vec<syn::Ident>, Box<dyn crate::Error>> {
+    let mut handlers = vec![];
+    for fn_item in fn_items {
+        if !fn_item.attrs.iter().any(|a| a.path().is_ident("command")) {
+            continue;
+        }
+
+        let info = match crate::CommandInfo::from_attributes(&fn_item.attrs) {
+            Ok(a) => a,
+            Err(e) => return Err(Box::new(e)),
+        };
+
+        for option in info.options {
+            let Some(handler) = option.autocomplete else {
+                continue;
+            };
+
+            if !fn_items.iter().any(|f| f.sig.ident == handler) {
+                return Err(Box::new(syn::Error::new(
+                    handler.span(),
+                    "Could not find the autocomplete handler in this controller",
+                )));
+            }
+            handlers.push(handler);
+        }
+    }
+
+    Ok(handlers)
+}
+
+pub(crate) fn generate_constraints(option: &crate::OptionInfo) -> Option<TokenStream> {
+    if option.min_value.is_none()
+        && option.max_value.is_none()
+        && option.min_length.is_none()
+        && option.max_length.is_none()
+        && option.channel_types.is_none()
+    {
+        return None;
+    }
+
+    let min_value = match &option.min_value {
+        Some(v) => quote! { Some((#v) as f64) },
+        None => quote! { None },
     };
+    let max_value = match &option.max_value {
+        Some(v) => quote! { Some((#v) as f64) },
+        None => quote! { None },
+    };
+    let min_length = match option.min_length.as_deref() {
+        Some(l) => quote! { Some(#l) },
+        None => quote! { None },
+    };
+    let max_length = match option.max_length.as_deref() {
+        Some(l) => quote! { Some(#l) },
+        None => quote! { None },
+    };
+    let channel_types = option
+        .channel_types
+        .iter()
+        .flat_map(|l| l.iter())
+        .map(|p| quote! { ::nightfall::export::twilight_model::channel::ChannelType::#p });
 
-    let typed = match arg {
-        syn::FnArg::Receiver(_) => {
-            *offset += 1;
-            return get_arg(args, index, offset, interaction_name);
+    Some(quote! {
+        ::nightfall::register::OptionConstraints {
+            min_value: #min_value,
+            max_value: #max_value,
+            min_length: #min_length,
+            max_length: #max_length,
+            channel_types: vec![#(#channel_types),*],
         }
-        syn::FnArg::Typed(t) => t,
+    })
+}
+
+pub(crate) fn generate_option_binding(
+    options_var: &TokenStream,
+    option_name: &str,
+    ty: &syn::Type,
+    constraints: Option<&TokenStream>,
+) -> TokenStream {
+    let inner = get_inner_optional(ty);
+    let expected = {
+        let ty = inner.as_ref().unwrap_or(ty);
+        quote! { <#ty as ::nightfall::register::CreateOption>::option_type() }
+    };
+    let missing = if inner.is_some() {
+        quote! { None }
+    } else {
+        quote! { return Err(::nightfall::Error::option_binding(data, #option_name, #expected, None)) }
     };
 
-    let syn::Pat::Ident(ident) = typed.pat.deref() else {
-        *offset += 1;
-        return get_arg(args, index, offset, interaction_name);
+    let check = constraints.map(|c| {
+        quote! {
+            if !(#c).is_satisfied_by(&v.value, data.resolved.as_ref()) {
+                return Err(::nightfall::Error::option_binding(data, #option_name, #expected, Some(&v.value)));
+            }
+        }
+    });
+
+    quote! {
+        match #options_var.iter().find(|o| o.name == #option_name) {
+            Some(v) => {
+                #check
+                match ::nightfall::FromOption::from_option(v.value.clone(), data) {
+                    Some(v2) => v2,
+                    None => return Err(::nightfall::Error::option_binding(data, #option_name, #expected, Some(&v.value)))
+                }
+            },
+            None => #missing,
+        }
+    }
+}
+
+/// Calls an autocomplete handler, the parameter for `focused_option` receives
+/// what the user has typed so far and the others are bound to the options of
+/// the command with the same parameter name
+fn generate_autocomplete_call(
+    fn_item: &ImplItemFn,
+    options_var: &TokenStream,
+    interaction_param: &str,
+    option_names: &HashMap<String, String>,
+    focused_option: &str,
+) -> Result<TokenStream, Box<dyn crate::Error>> {
+    let ident = &fn_item.sig.ident;
+
+    let mut args: Vec<TokenStream> = vec![];
+    let mut is_self = false;
+    for arg in fn_item.sig.inputs.iter() {
+        let ty = match arg {
+            syn::FnArg::Typed(t) => t,
+            syn::FnArg::Receiver(r) => {
+                check_receiver(r)?;
+                is_self = true;
+                continue;
+            }
+        };
+
+        let syn::Pat::Ident(i) = ty.pat.deref() else {
+            return Err(Box::new(syn::Error::new(
+                ty.span(),
+                "Unrecognised identifier",
+            )));
+        };
+
+        let arg_name = i.ident.to_string();
+        let option_name = option_names.get(&arg_name).unwrap_or(&arg_name);
+        if arg_name == interaction_param {
+            args.push(quote! { interaction })
+        } else if is_extract_param(ty) {
+            args.push(generate_extraction(&ty.ty));
+        } else if is_inject_param(ty) {
+            args.push(generate_injection(&ty.ty));
+        } else if is_context_param(ty) {
+            return Err(Box::new(syn::Error::new(
+                ty.span(),
+                "Autocomplete handlers can't respond through a context",
+            )));
+        } else if is_options_param(ty) {
+            return Err(Box::new(syn::Error::new(
+                ty.span(),
+                "Autocomplete handlers can't take an options struct",
+            )));
+        } else if option_name == focused_option {
+            args.push(quote! {
+                match focused_value.clone().and_then(|v| ::nightfall::FromOption::from_option(v, data)) {
+                    Some(v) => v,
+                    None => {
+                        return Err(::nightfall::Error::option_binding(
+                            data,
+                            focused_name,
+                            focused_kind,
+                            Some(focused),
+                        ))
+                    }
+                }
+            });
+        } else {
+            args.push(generate_option_binding(
+                options_var,
+                option_name,
+                &ty.ty,
+                None,
+            ));
+        }
+    }
+
+    Ok(if is_self {
+        quote! { self.#ident(#(#args),*).await }
+    } else {
+        quote! { Self::#ident(#(#args),*).await }
+    })
+}
+
+fn get_interaction_param(interaction: Option<&syn::Path>) -> String {
+    interaction
+        .and_then(|i| i.get_ident())
+        .map(|i| i.to_string())
+        .unwrap_or_else(|| String::from("interaction"))
+}
+
+/// The first paragraph of a doc comment with its lines joined by spaces
+pub(crate) fn get_doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines = vec![];
+    for attr in attrs {
+        let syn::Meta::NameValue(meta) = &attr.meta else {
+            continue;
+        };
+        let syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(doc),
+            ..
+        }) = &meta.value
+        else {
+            continue;
+        };
+        if !meta.path.is_ident("doc") {
+            continue;
+        }
+
+        // Block comments contain every line in one attribute
+        for line in doc.value().split('\n').map(str::trim) {
+            if !line.is_empty() {
+                lines.push(line.to_string());
+            } else if !lines.is_empty() {
+                return Some(lines.join(" "));
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
+
+// Doc comments are used as option descriptions but aren't allowed on parameters
+const PARAM_ATTRIBUTES: &[&str] = &["options", "extract", "inject", "doc"];
+
+fn is_options_param(arg: &syn::PatType) -> bool {
+    arg.attrs.iter().any(|a| a.path().is_ident("options"))
+}
+
+pub(crate) fn is_extract_param(arg: &syn::PatType) -> bool {
+    arg.attrs.iter().any(|a| a.path().is_ident("extract"))
+}
+
+pub(crate) fn is_inject_param(arg: &syn::PatType) -> bool {
+    arg.attrs.iter().any(|a| a.path().is_ident("inject"))
+}
+
+/// Whether the parameter is a `nightfall::Context`, the macro can't resolve
+/// paths so only `Context` and `nightfall::Context` are recognised
+pub(crate) fn is_context_param(arg: &syn::PatType) -> bool {
+    let syn::Type::Path(path) = arg.ty.deref() else {
+        return false;
     };
+    if path.qself.is_some() || path.path.segments.iter().any(|s| !s.arguments.is_none()) {
+        return false;
+    }
+
+    let segments = path
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        [name] => name == "Context" && path.path.leading_colon.is_none(),
+        [krate, name] => krate == "nightfall" && name == "Context",
+        _ => false,
+    }
+}
+
+pub(crate) fn generate_context() -> TokenStream {
+    quote! {
+        match ::nightfall::Context::new(interaction, services, acknowledgement) {
+            Some(v) => v,
+            None => return Err(::nightfall::Error::ContextUnavailable),
+        }
+    }
+}
+
+pub(crate) fn generate_injection(ty: &syn::Type) -> TokenStream {
+    quote! {
+        match <#ty as ::nightfall::Inject<S>>::inject(services) {
+            Some(v) => v,
+            None => return Err(::nightfall::Error::InjectionFailed),
+        }
+    }
+}
+
+pub(crate) fn generate_extraction(ty: &syn::Type) -> TokenStream {
+    quote! {
+        match <#ty as ::nightfall::FromInteraction>::from_interaction(interaction) {
+            Some(v) => v,
+            None => return Err(::nightfall::Error::ExtractionFailed),
+        }
+    }
+}
+
+/// Removes the attributes read from parameters, the compiler doesn't know
+/// them and would reject them otherwise
+fn strip_param_attributes(impl_: &mut ItemImpl) {
+    for item in impl_.items.iter_mut() {
+        let ImplItem::Fn(fn_item) = item else {
+            continue;
+        };
+
+        for arg in fn_item.sig.inputs.iter_mut() {
+            if let syn::FnArg::Typed(t) = arg {
+                t.attrs
+                    .retain(|a| !PARAM_ATTRIBUTES.iter().any(|p| a.path().is_ident(p)));
+            }
+        }
+    }
+}
+
+fn generate_localization_key(
+    kind: TokenStream,
+    path: &TokenStream,
+    choice: Option<&str>,
+    key: &str,
+) -> TokenStream {
+    let choice = match choice {
+        Some(c) => quote! { Some(#c) },
+        None => quote! { None },
+    };
+
+    quote! {
+        ::nightfall::localization::LocalizationKey {
+            kind: ::nightfall::export::twilight_model::application::command::CommandType::#kind,
+            path: #path,
+            choice: #choice,
+            key: #key,
+        }
+    }
+}
+
+pub(crate) fn generate_create_option(
+    option: &crate::OptionInfo,
+    name: &str,
+    description: &str,
+    ty: &syn::Type,
+) -> TokenStream {
+    let mut choices = vec![];
+    for choice in &option.choices {
+        let name = choice.name.as_str();
+        let value = &choice.value;
+        choices.push(quote! {(#name.into(), #value.into())})
+    }
+
+    let choice_vec = quote! { vec![#(#choices),*] };
+    let constraints = generate_constraints(option)
+        .unwrap_or_else(|| quote! { ::nightfall::register::OptionConstraints::default() });
+
+    let inner_optional = get_inner_optional(ty);
+    let required = inner_optional.is_none();
+    let ty = inner_optional.unwrap_or_else(|| ty.clone());
+
+    let stream = quote! {
+        <#ty as ::nightfall::register::CreateOption>::create_option(#name, #description, #required, #choice_vec, &#constraints)
+    };
+
+    if option.autocomplete.is_some() {
+        quote! {
+            {
+                let mut option = #stream;
+                option.autocomplete = Some(true);
+                option.choices = None;
+                option
+            }
+        }
+    } else {
+        stream
+    }
+}
+
+/// Generates an iterator over the localization keys of an option and its
+/// choices, `path` has to evaluate to the path of the option
+pub(crate) fn generate_option_localizations(
+    option: &crate::OptionInfo,
+    ty: &syn::Type,
+    path: &TokenStream,
+) -> TokenStream {
+    let mut keys = vec![];
+    if let Some(key) = &option.localization {
+        keys.push(generate_localization_key(
+            quote! { ChatInput },
+            path,
+            None,
+            key,
+        ));
+    }
+    for choice in &option.choices {
+        if let Some(key) = &choice.localization {
+            keys.push(generate_localization_key(
+                quote! { ChatInput },
+                path,
+                Some(choice.name.as_str()),
+                key,
+            ));
+        }
+    }
 
-    if &ident.ident.to_string() == interaction_name {
-        *offset += 1;
-        return get_arg(args, index, offset, interaction_name);
+    let ty = get_inner_optional(ty).unwrap_or_else(|| ty.clone());
+
+    // Choices that come from the option type, such as a derived `Choice`
+    quote! {
+        [#(#keys),*].into_iter().chain(
+            <#ty as ::nightfall::register::CreateOption>::choice_localizations()
+                .iter()
+                .map(|(choice, key)| ::nightfall::localization::LocalizationKey {
+                    kind: ::nightfall::export::twilight_model::application::command::CommandType::ChatInput,
+                    path: #path,
+                    choice: Some(choice),
+                    key,
+                })
+        )
     }
+}
+
+/// Builder calls for the permissions and flags of a top level command, values
+/// set on the method take precedence over the ones set on the controller
+fn generate_command_flags(
+    config: &crate::CommandControllerConfig,
+    permissions: Option<&darling::util::PathList>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+) -> TokenStream {
+    let permissions = permissions
+        .or(config.default_member_permissions.as_ref())
+        .map(|p| {
+            let p = p.iter();
+            quote! {
+                .default_member_permissions(
+                    ::nightfall::export::twilight_model::guild::Permissions::empty()
+                        #(| ::nightfall::export::twilight_model::guild::Permissions::#p)*
+                )
+            }
+        });
+    let dm_permission = dm_permission
+        .or(config.dm_permission)
+        .map(|d| quote! { .dm_permission(#d) });
+    let nsfw = nsfw.or(config.nsfw).map(|n| quote! { .nsfw(#n) });
 
-    Some(arg.clone())
+    quote! { #permissions #dm_permission #nsfw }
 }
 
 fn generate_register_command(
     impl_: &ItemImpl,
     args: &crate::CommandControllerConfig,
-) -> Result<TokenStream, Box<dyn crate::Error>> {
+) -> Result<(TokenStream, TokenStream), Box<dyn crate::Error>> {
     let fn_items: Vec<&ImplItemFn> = impl_
         .items
         .iter()
@@ -73,111 +578,333 @@ fn generate_register_command(
         })
         .collect();
 
+    let autocomplete_handlers = get_autocomplete_handlers(&fn_items)?;
+
+    // Sub commands of the controller count towards the limit of the command
+    // they are part of, other controllers can add more
+    let mut length = 0;
+    if let (Some(sub), Some(description)) = (&args.sub, &args.sub_description) {
+        check_name(sub, sub.span())?;
+        check_description(description, description.span())?;
+        length += sub.chars().count() + description.chars().count();
+    }
+    if let (Some(group), Some(description)) = (&args.group, &args.group_description) {
+        check_name(group, group.span())?;
+        check_description(description, description.span())?;
+        length += group.chars().count() + description.chars().count();
+    }
+
+    let base_path: Vec<String> = [args.sub.as_deref(), args.group.as_deref()]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+
+    // Every entry is something that can be iterated over for localization keys
+    let mut localization_keys = vec![];
+    if let (Some(sub), Some(key)) = (args.sub.as_deref(), &args.sub_localization) {
+        let key =
+            generate_localization_key(quote! { ChatInput }, &quote! { vec![#sub] }, None, key);
+        localization_keys.push(quote! { [#key] });
+    }
+    if let (Some(_), Some(key)) = (&args.group, &args.group_localization) {
+        let key = generate_localization_key(
+            quote! { ChatInput },
+            &quote! { vec![#(#base_path),*] },
+            None,
+            key,
+        );
+        localization_keys.push(quote! { [#key] });
+    }
+
     let mut command_streams = vec![];
+    let mut sub_command_names = vec![];
+    let mut context_menu_streams = vec![];
     for fn_item in fn_items {
+        if autocomplete_handlers.contains(&fn_item.sig.ident) || get_pattern(fn_item).is_some() {
+            continue;
+        }
+
+        if let Some(kind) = get_context_menu_kind(fn_item) {
+            let info = match crate::ContextMenuInfo::from_attributes(&fn_item.attrs) {
+                Ok(a) => a,
+                Err(e) => return Err(Box::new(e)),
+            };
+
+            let name = info
+                .name
+                .as_deref()
+                .cloned()
+                .unwrap_or(fn_item.sig.ident.to_string());
+            check_context_menu_name(
+                &name,
+                value_span(info.name.as_ref(), fn_item.sig.ident.span()),
+            )?;
+
+            if let Some(key) = &info.localization {
+                let key =
+                    generate_localization_key(kind.clone(), &quote! { vec![#name] }, None, key);
+                localization_keys.push(quote! { [#key] });
+            }
+
+            let flags = generate_command_flags(
+                args,
+                info.default_member_permissions.as_ref(),
+                info.dm_permission,
+                info.nsfw,
+            );
+
+            context_menu_streams.push(quote! {
+                ::nightfall::export::twilight_util::builder::command::CommandBuilder::new(
+                    #name,
+                    "",
+                    ::nightfall::export::twilight_model::application::command::CommandType::#kind,
+                )
+                #flags
+                .build()
+            });
+            continue;
+        }
+
         let info = match crate::CommandInfo::from_attributes(&fn_item.attrs) {
             Ok(a) => a,
             Err(e) => return Err(Box::new(e)),
         };
 
-        let interaction_param = info
-            .interaction
-            .as_ref()
-            .and_then(|i| i.get_ident())
-            .map(|i| i.to_string())
-            .unwrap_or_else(|| String::from("interaction"));
+        let interaction_param = get_interaction_param(info.interaction.as_ref());
+        let name = info
+            .name
+            .as_deref()
+            .cloned()
+            .unwrap_or(fn_item.sig.ident.to_string());
 
-        let mut options = vec![];
-        let mut offset = 0;
-        for (i, option) in info.options.into_iter().enumerate() {
-            let arg = match get_arg(&fn_item.sig.inputs, i, &mut offset, &interaction_param) {
-                Some(a) => a,
-                None => {
-                    return Err(Box::new(syn::Error::new(
-                        fn_item.span(),
-                        "More options than there are arguments",
-                    )))
-                }
-            };
+        let span = value_span(info.name.as_ref(), fn_item.sig.ident.span());
+        let truncate = info
+            .truncate_descriptions
+            .unwrap_or(args.truncate_descriptions);
+        check_name(&name, span)?;
+        let description = resolve_description(
+            info.description.as_deref(),
+            get_doc_comment(&fn_item.attrs),
+            truncate,
+            value_span(info.description.as_ref(), fn_item.sig.ident.span()),
+        )?;
+        if args.sub.is_some() {
+            sub_command_names.push(name.clone());
+            check_sub_commands(&sub_command_names, span)?;
+        }
+        let mut command_length = name.chars().count() + description.chars().count();
 
-            let mut choices = vec![];
-            for choice in option.choices {
-                let name = choice.name;
-                let value = choice.value;
-                choices.push(quote! {(#name.into(), #value.into())})
-            }
+        let has_flags = info.default_member_permissions.is_some()
+            || info.dm_permission.is_some()
+            || info.nsfw.is_some();
+        if has_flags && args.sub.is_some() {
+            return Err(Box::new(syn::Error::new(
+                fn_item.sig.ident.span(),
+                "Permissions and flags of sub commands have to be set on the controller",
+            )));
+        }
+
+        let mut command_path = base_path.clone();
+        command_path.push(name.clone());
+        if let Some(key) = &info.localization {
+            let key = generate_localization_key(
+                quote! { ChatInput },
+                &quote! { vec![#(#command_path),*] },
+                None,
+                key,
+            );
+            localization_keys.push(quote! { [#key] });
+        }
 
+        // Every entry is something that can be iterated over for options
+        let mut options = vec![];
+        let mut option_names = vec![];
+        let mut option_infos = info.options.iter();
+        for arg in fn_item.sig.inputs.iter() {
             let syn::FnArg::Typed(typed) = arg else {
-                todo!();
+                continue;
             };
 
-            let choice_vec = quote! { vec![#(#choices),*] };
             let syn::Pat::Ident(ident) = typed.pat.deref() else {
-                todo!();
+                return Err(Box::new(syn::Error::new(
+                    typed.span(),
+                    "Unrecognised identifier",
+                )));
             };
 
-            let name = option.name.clone().unwrap_or(ident.ident.to_string());
-            let description = &option.description;
+            if ident.ident == interaction_param
+                || is_context_param(typed)
+                || is_extract_param(typed)
+                || is_inject_param(typed)
+            {
+                continue;
+            }
 
-            let inner_optional = get_inner_optional(&typed.ty);
-            let stream = if let Some(t) = inner_optional {
-                quote! {
-                    <#t as CreateOption>::create_option(#name, #description, true)
-                }
-            } else {
-                let ty = &typed.ty;
-                quote! {
-                    <#ty as ::nightfall::register::CreateOption>::create_option(#name, #description, true, #choice_vec)
-                }
-            };
+            let ty = &typed.ty;
+            if is_options_param(typed) {
+                options.push(quote! { <#ty as ::nightfall::CommandOptions>::create_options() });
+                localization_keys.push(quote! {
+                    <#ty as ::nightfall::CommandOptions>::localization_keys(&[#(#command_path),*])
+                });
+                continue;
+            }
+
+            // Options without an entry in the attribute are described by their doc comment
+            let default = crate::OptionInfo::default();
+            let option = option_infos.next().unwrap_or(&default);
+
+            if option.autocomplete.is_some() && !option.choices.is_empty() {
+                return Err(Box::new(syn::Error::new(
+                    fn_item.span(),
+                    "An option can not have both choices and autocomplete",
+                )));
+            }
+
+            let name = option
+                .name
+                .as_deref()
+                .cloned()
+                .unwrap_or(ident.ident.to_string());
+            let name_span = value_span(option.name.as_ref(), ident.span());
+            check_option(option, &name, name_span)?;
+            let description = resolve_description(
+                option.description.as_deref(),
+                get_doc_comment(&typed.attrs),
+                truncate,
+                value_span(option.description.as_ref(), typed.span()),
+            )?;
+            option_names.push((name.clone(), get_inner_optional(ty).is_none(), name_span));
+            command_length += option_length(option, &name, &description);
+
+            localization_keys.push(generate_option_localizations(
+                option,
+                ty,
+                &quote! { vec![#(#command_path,)* #name] },
+            ));
 
-            options.push(stream);
+            let stream = generate_create_option(option, &name, &description, ty);
+            options.push(quote! { [#stream] });
         }
 
-        let name = info.name.unwrap_or(fn_item.sig.ident.to_string());
-        let description = info.description;
+        if option_infos.next().is_some() {
+            return Err(Box::new(syn::Error::new(
+                fn_item.span(),
+                "More options than there are arguments",
+            )));
+        }
 
-        let command = if args.sub.is_some() {
+        // Options of `#[options]` parameters are checked with these by `build_commands`
+        check_options(&option_names, span)?;
+        if args.sub.is_some() {
+            length += command_length;
+            check_command_length(&base_path[0], length, span)?;
+        } else {
+            check_command_length(&name, command_length, span)?;
+        }
+
+        let options = quote! {
+            ::std::iter::empty::<::nightfall::export::twilight_model::application::command::CommandOption>()
+                #(.chain(#options))*
+        };
+
+        let command = if args.group.is_some() {
             quote! {
-                ::nightfall::export::twilight_util::builder::command::SubCommandBuilder::new(
-                    #name,
-                    #description,
+                #options.fold(
+                    ::nightfall::export::twilight_util::builder::command::SubCommandBuilder::new(
+                        #name,
+                        #description,
+                    ),
+                    |builder, option| builder.option(option),
+                )
+            }
+        } else if args.sub.is_some() {
+            quote! {
+                #options.fold(
+                    ::nightfall::export::twilight_util::builder::command::SubCommandBuilder::new(
+                        #name,
+                        #description,
+                    ),
+                    |builder, option| builder.option(option),
                 )
-                #(.option(#options))*
                 .build()
             }
         } else {
+            let flags = generate_command_flags(
+                args,
+                info.default_member_permissions.as_ref(),
+                info.dm_permission,
+                info.nsfw,
+            );
+
             quote! {
-                ::nightfall::export::twilight_util::builder::command::CommandBuilder::new(
-                    #name,
-                    #description,
-                    ::nightfall::export::twilight_model::application::command::CommandType::ChatInput,
+                #options.fold(
+                    ::nightfall::export::twilight_util::builder::command::CommandBuilder::new(
+                        #name,
+                        #description,
+                        ::nightfall::export::twilight_model::application::command::CommandType::ChatInput,
+                    ),
+                    |builder, option| builder.option(option),
                 )
-                #(.option(#options))*
+                #flags
                 .build()
             }
         };
         command_streams.push(command);
     }
 
-    let commands = if let Some(sub) = args.sub.as_ref() {
-        let sub_description = args.sub_description.as_ref().unwrap();
+    let flags = generate_command_flags(args, None, None, None);
+    let mut commands = if command_streams.is_empty() {
+        vec![]
+    } else if let (Some(sub), Some(group)) = (args.sub.as_deref(), args.group.as_deref()) {
+        let sub_description = args.sub_description.as_deref().unwrap();
+        let group_description = args.group_description.as_deref().unwrap();
 
-        quote! {
-            vec![::nightfall::export::twilight_util::builder::command::CommandBuilder::new(
+        vec![quote! {
+            ::nightfall::export::twilight_util::builder::command::CommandBuilder::new(
+                #sub,
+                #sub_description,
+                ::nightfall::export::twilight_model::application::command::CommandType::ChatInput,
+            )
+            .option(
+                ::nightfall::export::twilight_util::builder::command::SubCommandGroupBuilder::new(
+                    #group,
+                    #group_description,
+                )
+                .subcommands([#(#command_streams),*])
+                .build()
+            )
+            #flags
+            .build()
+        }]
+    } else if let Some(sub) = args.sub.as_deref() {
+        let sub_description = args.sub_description.as_deref().unwrap();
+
+        vec![quote! {
+            ::nightfall::export::twilight_util::builder::command::CommandBuilder::new(
                 #sub,
                 #sub_description,
                 ::nightfall::export::twilight_model::application::command::CommandType::ChatInput,
             )
             #(.option(#command_streams))*
-            .build()]
-        }
+            #flags
+            .build()
+        }]
     } else {
-        quote! {
-            vec![#(#command_streams),*]
-        }
+        command_streams
     };
-    Ok(commands)
+    commands.append(&mut context_menu_streams);
+
+    Ok((
+        quote! {
+            vec![#(#commands),*]
+        },
+        quote! {
+            ::std::iter::empty()
+                #(.chain(#localization_keys))*
+                .collect()
+        },
+    ))
 }
 
 pub(crate) fn generate_command_controller(
@@ -199,54 +926,124 @@ pub(crate) fn generate_command_controller(
         return Err(Box::new(syn::Error::new(impl_.self_ty.span(), "")));
     };
 
-    let mut command_names = vec![];
+    let autocomplete_handlers = get_autocomplete_handlers(&fn_items)?;
+
+    let mut auto_defer_commands = vec![];
+    let mut ephemeral_commands = vec![];
     let mut statements = quote! {};
+    let mut context_menu_statements = quote! {};
+    let mut autocomplete_statements = quote! {};
+    let mut component_statements = quote! {};
+    let mut component_patterns = vec![];
+    let mut modal_statements = quote! {};
+    let mut modal_patterns = vec![];
     let options_var = if args.sub.is_some() {
         quote! { sub_options }
     } else {
         quote! { data.options }
     };
-    let name_var = if args.sub.is_some() {
+    let is_sub = args.sub.is_some();
+    let name_var = if is_sub {
         quote! { sub_name }
     } else {
         quote! { data.name }
     };
 
-    for fn_item in fn_items {
+    for fn_item in fn_items.iter() {
         let ident = &fn_item.sig.ident;
-        let name = fn_item.sig.ident.to_string();
 
-        let mut args: Vec<TokenStream> = vec![];
-        let mut is_self = false;
-        let mut offset = 0;
-        for (i, arg) in fn_item.sig.inputs.iter().enumerate() {
-            let info = match crate::CommandInfo::from_attributes(&fn_item.attrs) {
+        if autocomplete_handlers.contains(ident) {
+            continue;
+        }
+
+        if let Some((kind, pattern)) = get_pattern(fn_item) {
+            let pattern = match pattern {
+                Ok(p) => p,
+                Err(e) => return Err(Box::new(e)),
+            };
+            let statement = generate_pattern_statement(fn_item, kind, &pattern)?;
+
+            match kind {
+                PatternKind::Component => {
+                    component_statements = quote! {
+                        #component_statements
+                        #statement
+                    };
+                    component_patterns.push(pattern);
+                }
+                PatternKind::Modal => {
+                    modal_statements = quote! {
+                        #modal_statements
+                        #statement
+                    };
+                    modal_patterns.push(pattern);
+                }
+            }
+            continue;
+        }
+
+        if let Some(kind) = get_context_menu_kind(fn_item) {
+            let info = match crate::ContextMenuInfo::from_attributes(&fn_item.attrs) {
                 Ok(a) => a,
                 Err(e) => return Err(Box::new(e)),
             };
 
-            let interaction_param = info
-                .interaction
-                .as_ref()
-                .and_then(|i| i.get_ident())
-                .map(|i| i.to_string())
-                .unwrap_or_else(|| String::from("interaction"));
+            let interaction_param = get_interaction_param(info.interaction.as_ref());
+            let name = info.name.as_deref().cloned().unwrap_or(ident.to_string());
+            let call = generate_context_menu_call(fn_item, &interaction_param)?;
+            if info.ephemeral.unwrap_or(args.ephemeral) {
+                ephemeral_commands.push(quote! {
+                    (::nightfall::export::twilight_model::application::command::CommandType::#kind, #name)
+                });
+            }
+
+            context_menu_statements = quote! {
+                #context_menu_statements
+                if data.kind == ::nightfall::export::twilight_model::application::command::CommandType::#kind
+                    && data.name == #name
+                {
+                    return match #call {
+                        Ok(r) => Ok(::nightfall::response::IntoResponse::into_response(r)),
+                        Err(e) => Err(::std::convert::Into::<::nightfall::Error>::into(e)),
+                    };
+                }
+            };
+            continue;
+        }
 
+        let info = match crate::CommandInfo::from_attributes(&fn_item.attrs) {
+            Ok(a) => a,
+            Err(e) => return Err(Box::new(e)),
+        };
+
+        let interaction_param = get_interaction_param(info.interaction.as_ref());
+        let name = info.name.as_deref().cloned().unwrap_or(ident.to_string());
+
+        let route = [args.sub.as_deref(), args.group.as_deref(), Some(&name)]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(auto_defer) = info.auto_defer {
+            auto_defer_commands.push(quote! { (#route, #auto_defer) });
+        }
+        if info.ephemeral.unwrap_or(args.ephemeral) {
+            ephemeral_commands.push(quote! {
+                (::nightfall::export::twilight_model::application::command::CommandType::ChatInput, #route)
+            });
+        }
+
+        let mut args: Vec<TokenStream> = vec![];
+        let mut is_self = false;
+        let mut offset = 0;
+        let mut option_names = HashMap::new();
+        let mut autocompletes = vec![];
+        for (i, arg) in fn_item.sig.inputs.iter().enumerate() {
             let ty = match arg {
                 syn::FnArg::Typed(t) => t,
                 syn::FnArg::Receiver(r) => {
-                    if r.mutability.is_some() {
-                        return Err(Box::new(syn::Error::new(
-                            arg.span(),
-                            "Self must be non mutable reference", // Dependency injection only gives us a non mutable arc
-                        )));
-                    } else if r.reference.is_none() {
-                        return Err(Box::new(syn::Error::new(
-                            arg.span(),
-                            "Self must be reference", // We can not move it cause it is a part of a Arc that is managed by the dependency injection
-                        )));
-                    }
-
+                    check_receiver(r)?;
                     is_self = true;
                     offset += 1;
                     continue;
@@ -265,28 +1062,76 @@ pub(crate) fn generate_command_controller(
             if arg_name == interaction_param {
                 offset += 1;
                 args.push(quote! { interaction })
-            } else {
-                let Some(opt_info) = info.options.get(i - offset) else {
-                    return Err(Box::new(syn::Error::new(
-                        fn_item.span(),
-                        "More options than there are arguments",
-                    )));
-                };
-                let arg_name = opt_info.name.as_ref().unwrap_or(&arg_name);
-
+            } else if is_extract_param(ty) {
+                offset += 1;
+                args.push(generate_extraction(&ty.ty));
+            } else if is_inject_param(ty) {
+                offset += 1;
+                args.push(generate_injection(&ty.ty));
+            } else if is_context_param(ty) {
+                offset += 1;
+                args.push(generate_context());
+            } else if is_options_param(ty) {
+                offset += 1;
+                let ty = &ty.ty;
+                // Bound in its own statement so the result isn't held across the await
                 args.push(quote! {
-                    match #options_var.iter().find(|o| o.name == #arg_name) {
-                        Some(v) => match ::nightfall::FromOption::from_option(v.value.clone()) {
-                            Some(v2) => v2,
-                            None => return Err(::nightfall::Error::OptionBindingFailed)
-                        },
-                        None => return Err(::nightfall::Error::OptionBindingFailed),
+                    {
+                        let options = <#ty as ::nightfall::CommandOptions>::from_options(&#options_var, data);
+                        match options {
+                            Ok(v) => v,
+                            Err(e) => return Err(e),
+                        }
                     }
                 });
+            } else {
+                let default = crate::OptionInfo::default();
+                let opt_info = info.options.get(i - offset).unwrap_or(&default);
+                let option_name = opt_info
+                    .name
+                    .as_deref()
+                    .cloned()
+                    .unwrap_or(arg_name.clone());
+                option_names.insert(arg_name, option_name.clone());
+                let arg_name = &option_name;
+
+                if let Some(handler) = opt_info.autocomplete.as_ref() {
+                    autocompletes.push((handler.clone(), option_name.clone()));
+                }
+
+                let constraints = generate_constraints(opt_info);
+                args.push(generate_option_binding(
+                    &options_var,
+                    arg_name,
+                    &ty.ty,
+                    constraints.as_ref(),
+                ));
             }
         }
 
-        command_names.push(ident.to_string());
+        // Generated after the loop so the handler can bind options that come
+        // after the one it completes
+        for (handler, option_name) in autocompletes {
+            let handler_fn = fn_items.iter().find(|f| f.sig.ident == handler).unwrap();
+            let handler_call = generate_autocomplete_call(
+                handler_fn,
+                &options_var,
+                &interaction_param,
+                &option_names,
+                &option_name,
+            )?;
+
+            autocomplete_statements = quote! {
+                #autocomplete_statements
+                if #name_var == #name && focused_name == #option_name {
+                    return match #handler_call {
+                        Ok(choices) => Ok(choices),
+                        Err(e) => Err(::std::convert::Into::<::nightfall::Error>::into(e)),
+                    };
+                }
+            };
+        }
+
         let call = if is_self {
             quote! { self.#ident(#(#args),*).await }
         } else {
@@ -297,30 +1142,77 @@ pub(crate) fn generate_command_controller(
             #statements
             if #name_var == #name {
                 return match #call {
-                    Ok(()) => Ok(()),
-                    Err(e) => Err(::nightfall::Error::CommandError { error: e }),
+                    Ok(r) => Ok(::nightfall::response::IntoResponse::into_response(r)),
+                    Err(e) => Err(::std::convert::Into::<::nightfall::Error>::into(e)),
                 };
             }
         }
     }
 
-    let register = generate_register_command(&impl_, &args)?;
+    let (register, localization_keys) = generate_register_command(&impl_, &args)?;
 
-    let get_command_names = if let Some(sub) = args.sub.as_ref() {
-        quote! {
-            fn get_command_names<'a>() -> &'a[&'static str] {
-                &[#sub]
-            }
+    let get_auto_defer_commands = quote! {
+        fn get_auto_defer_commands<'a>() -> &'a [(&'static str, bool)] {
+            &[#(#auto_defer_commands),*]
         }
-    } else {
-        quote! {
-            fn get_command_names<'a>() -> &'a[&'static str] {
-                &[#(#command_names),*]
-            }
+    };
+
+    let get_ephemeral_commands = quote! {
+        fn get_ephemeral_commands<'a>() -> &'a [(
+            ::nightfall::export::twilight_model::application::command::CommandType,
+            &'static str,
+        )] {
+            &[#(#ephemeral_commands),*]
+        }
+    };
+
+    let ephemeral = args.ephemeral;
+    let is_ephemeral = quote! {
+        fn is_ephemeral() -> bool {
+            #ephemeral
         }
     };
 
-    let execute_command = if let Some(sub) = args.sub.as_ref() {
+    let prelude = if let (Some(sub), Some(group)) = (args.sub.as_deref(), args.group.as_deref()) {
+        quote! {
+            if data.name != #sub {
+                return Err(::nightfall::Error::CommandNotFound);
+            }
+
+            let Some(group) = data.options.first() else {
+                return Err(::nightfall::Error::CommandNotFound);
+            };
+
+            if group.name != #group {
+                return Err(::nightfall::Error::CommandNotFound);
+            }
+
+            let ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::SubCommandGroup(group_options)
+                = &group.value else {
+                return Err(::nightfall::Error::option_binding(
+                    data,
+                    &group.name,
+                    ::nightfall::export::twilight_model::application::command::CommandOptionType::SubCommandGroup,
+                    Some(&group.value),
+                ))
+            };
+
+            let Some(sub) = group_options.first() else {
+                return Err(::nightfall::Error::CommandNotFound);
+            };
+
+            let sub_name = sub.name.clone();
+            let ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::SubCommand(sub_options)
+                = &sub.value else {
+                return Err(::nightfall::Error::option_binding(
+                    data,
+                    &sub.name,
+                    ::nightfall::export::twilight_model::application::command::CommandOptionType::SubCommand,
+                    Some(&sub.value),
+                ))
+            };
+        }
+    } else if let Some(sub) = args.sub.as_deref() {
         quote! {
             if data.name != #sub {
                 return Err(::nightfall::Error::CommandNotFound);
@@ -333,33 +1225,164 @@ pub(crate) fn generate_command_controller(
             let sub_name = sub.name.clone();
             let ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::SubCommand(sub_options)
                 = &sub.value else {
-                return Err(::nightfall::Error::OptionBindingFailed)
+                return Err(::nightfall::Error::option_binding(
+                    data,
+                    &sub.name,
+                    ::nightfall::export::twilight_model::application::command::CommandOptionType::SubCommand,
+                    Some(&sub.value),
+                ))
             };
+        }
+    } else {
+        quote! {}
+    };
 
-            #statements
-            Err(::nightfall::Error::CommandNotFound)
+    let execute_component = if component_patterns.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            async fn execute_component(
+                &self,
+                interaction: &::nightfall::export::twilight_model::gateway::payload::incoming::InteractionCreate,
+                data: &::nightfall::export::twilight_model::application::interaction::message_component::MessageComponentInteractionData,
+                services: &S,
+                acknowledgement: &::nightfall::response::Acknowledgement,
+            ) -> Result<Option<::nightfall::export::twilight_model::http::interaction::InteractionResponse>, ::nightfall::Error> {
+                #component_statements
+                Err(::nightfall::Error::ComponentNotFound)
+            }
         }
+    };
+    let get_component_patterns = if component_patterns.is_empty() {
+        quote! {}
     } else {
-        quote! { 
-            #statements
-            Err(::nightfall::Error::CommandNotFound)
+        quote! {
+            fn get_component_patterns<'a>() -> &'a [&'static str] {
+                &[#(#component_patterns),*]
+            }
+        }
+    };
+
+    let execute_modal = if modal_patterns.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            async fn execute_modal(
+                &self,
+                interaction: &::nightfall::export::twilight_model::gateway::payload::incoming::InteractionCreate,
+                data: &::nightfall::export::twilight_model::application::interaction::modal::ModalInteractionData,
+                services: &S,
+                acknowledgement: &::nightfall::response::Acknowledgement,
+            ) -> Result<Option<::nightfall::export::twilight_model::http::interaction::InteractionResponse>, ::nightfall::Error> {
+                #modal_statements
+                Err(::nightfall::Error::ModalNotFound)
+            }
+        }
+    };
+    let get_modal_patterns = if modal_patterns.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn get_modal_patterns<'a>() -> &'a [&'static str] {
+                &[#(#modal_patterns),*]
+            }
         }
     };
 
+    let execute_autocomplete = if autocomplete_statements.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            async fn execute_autocomplete(
+                &self,
+                interaction: &::nightfall::export::twilight_model::gateway::payload::incoming::InteractionCreate,
+                data: &::nightfall::export::twilight_model::application::interaction::application_command::CommandData,
+                services: &S,
+            ) -> Result<Vec<::nightfall::export::twilight_model::application::command::CommandOptionChoice>, ::nightfall::Error> {
+                #prelude
+
+                let Some((focused_name, focused_value, focused_kind, focused)) = #options_var.iter().find_map(|o| match &o.value {
+                    ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::Focused(v, kind) => {
+                        Some((o.name.as_str(), v.as_str(), *kind, &o.value))
+                    }
+                    _ => None,
+                }) else {
+                    return Err(::nightfall::Error::NotAutocomplete);
+                };
+
+                // Discord sends what was typed as a string whatever the type of the option
+                let focused_value = match focused_kind {
+                    ::nightfall::export::twilight_model::application::command::CommandOptionType::Integer => focused_value
+                        .parse()
+                        .ok()
+                        .map(::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::Integer),
+                    ::nightfall::export::twilight_model::application::command::CommandOptionType::Number => focused_value
+                        .parse()
+                        .ok()
+                        .map(::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::Number),
+                    _ => Some(
+                        ::nightfall::export::twilight_model::application::interaction::application_command::CommandOptionValue::String(
+                            focused_value.to_string(),
+                        ),
+                    ),
+                };
+
+                #autocomplete_statements
+                Err(::nightfall::Error::CommandNotFound)
+            }
+        }
+    };
+
+    let mut stripped_impl = impl_.clone();
+    strip_param_attributes(&mut stripped_impl);
+
     Ok(quote! {
-        #impl_
+        #stripped_impl
 
         #[::nightfall::export::async_trait::async_trait]
-        impl ::nightfall::CommandController for #struct_name {
+        impl<S> ::nightfall::CommandController<S> for #struct_name
+        where
+            S: ::nightfall::export::deppy::ServiceHandler + Sync,
+        {
             async fn execute_command(
                 &self,
                 interaction: &::nightfall::export::twilight_model::gateway::payload::incoming::InteractionCreate,
                 data: &::nightfall::export::twilight_model::application::interaction::application_command::CommandData,
-            ) -> Result<(), ::nightfall::Error> {
-                #execute_command
+                services: &S,
+                acknowledgement: &::nightfall::response::Acknowledgement,
+            ) -> Result<Option<::nightfall::export::twilight_model::http::interaction::InteractionResponse>, ::nightfall::Error> {
+                #context_menu_statements
+
+                if data.kind != ::nightfall::export::twilight_model::application::command::CommandType::ChatInput {
+                    return Err(::nightfall::Error::CommandNotFound);
+                }
+
+                #prelude
+                #statements
+                Err(::nightfall::Error::CommandNotFound)
             }
 
-            #get_command_names
+            #execute_autocomplete
+
+            #execute_component
+
+            #execute_modal
+        }
+
+        impl ::nightfall::ControllerMetadata for #struct_name {
+            #get_auto_defer_commands
+
+            #get_ephemeral_commands
+
+            #is_ephemeral
+
+            #get_component_patterns
+
+            #get_modal_patterns
+
+            fn get_localization_keys() -> Vec<::nightfall::localization::LocalizationKey> {
+                #localization_keys
+            }
 
             fn build_commands() -> Vec<::nightfall::export::twilight_model::application::command::Command> {
                 #register
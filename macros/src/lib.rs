@@ -1,11 +1,20 @@
 extern crate proc_macro;
+mod choice;
+mod component;
 mod generate;
+mod modal;
+mod options;
+mod validate;
 
+use crate::choice::generate_choice;
 use crate::generate::generate_command_controller;
+use crate::modal::generate_modal;
+use crate::options::generate_command_options;
+use darling::util::SpannedValue;
 use darling::FromMeta;
 use darling::{ast::NestedMeta, FromAttributes};
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, ItemImpl};
+use syn::{parse_macro_input, DeriveInput, ItemImpl};
 
 pub(crate) trait Error {
     fn write_errors(&self) -> proc_macro2::TokenStream;
@@ -25,33 +34,70 @@ impl Error for darling::Error {
 
 #[derive(Debug, FromMeta)]
 pub(crate) struct CommandControllerConfig {
-    sub: Option<String>,
-    sub_description: Option<String>,
-    group: Option<String>,
+    sub: Option<SpannedValue<String>>,
+    sub_description: Option<SpannedValue<String>>,
+    group: Option<SpannedValue<String>>,
+    group_description: Option<SpannedValue<String>>,
+    sub_localization: Option<String>,
+    group_localization: Option<String>,
+    default_member_permissions: Option<darling::util::PathList>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+    #[darling(default)]
+    truncate_descriptions: bool,
+    #[darling(default)]
+    ephemeral: bool,
 }
 
 #[derive(Debug, FromMeta)]
 pub(crate) struct ChoiceInfo {
-    name: String,
+    name: SpannedValue<String>,
     value: syn::Lit,
+    localization: Option<String>,
 }
 
-#[derive(Debug, FromMeta)]
+#[derive(Debug, Default, FromMeta)]
 pub(crate) struct OptionInfo {
-    name: Option<String>,
-    description: String,
+    name: Option<SpannedValue<String>>,
+    description: Option<SpannedValue<String>>,
     #[darling(multiple, rename = "choice")]
     choices: Vec<ChoiceInfo>,
+    autocomplete: Option<syn::Ident>,
+    min_value: Option<syn::Expr>,
+    max_value: Option<syn::Expr>,
+    min_length: Option<SpannedValue<u16>>,
+    max_length: Option<SpannedValue<u16>>,
+    channel_types: Option<darling::util::PathList>,
+    localization: Option<String>,
 }
 
 #[derive(Debug, FromAttributes)]
 #[darling(attributes(command))]
 pub(crate) struct CommandInfo {
-    name: Option<String>,
-    description: String,
+    name: Option<SpannedValue<String>>,
+    description: Option<SpannedValue<String>>,
     #[darling(multiple, rename = "option")]
     options: Vec<OptionInfo>,
     interaction: Option<syn::Path>,
+    localization: Option<String>,
+    default_member_permissions: Option<darling::util::PathList>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+    truncate_descriptions: Option<bool>,
+    auto_defer: Option<bool>,
+    ephemeral: Option<bool>,
+}
+
+#[derive(Debug, FromAttributes)]
+#[darling(attributes(user_command, message_command))]
+pub(crate) struct ContextMenuInfo {
+    name: Option<SpannedValue<String>>,
+    interaction: Option<syn::Path>,
+    localization: Option<String>,
+    default_member_permissions: Option<darling::util::PathList>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+    ephemeral: Option<bool>,
 }
 
 #[proc_macro_attribute]
@@ -59,6 +105,56 @@ pub fn command(_: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+#[proc_macro_attribute]
+pub fn user_command(_: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+#[proc_macro_attribute]
+pub fn message_command(_: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+#[proc_macro_attribute]
+pub fn component(_: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+#[proc_macro_attribute]
+pub fn modal(_: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+#[proc_macro_derive(Modal, attributes(form, text_input))]
+pub fn derive_modal(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    match generate_modal(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+#[proc_macro_derive(Choice, attributes(choice))]
+pub fn derive_choice(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    match generate_choice(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+#[proc_macro_derive(CommandOptions, attributes(command_options, option))]
+pub fn derive_command_options(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    match generate_command_options(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
 #[proc_macro_attribute]
 pub fn command_controller(attr: TokenStream, item: TokenStream) -> TokenStream {
     let impl_ = parse_macro_input!(item as ItemImpl);
@@ -71,24 +167,33 @@ pub fn command_controller(attr: TokenStream, item: TokenStream) -> TokenStream {
         Err(e) => return e.write_errors().into(),
     };
 
-    if config.group.is_some() && config.sub.is_none() {
+    if let (Some(group), None) = (&config.group, &config.sub) {
         return syn::Error::new(
-            proc_macro2::Span::call_site(),
+            group.span(),
             "Cannot specify group alone, sub needs to be specified if group is specified",
         )
         .to_compile_error()
         .into();
     }
 
-    if config.sub.is_some() && config.sub_description.is_none() {
+    if let (Some(sub), None) = (&config.sub, &config.sub_description) {
         return syn::Error::new(
-            proc_macro2::Span::call_site(),
+            sub.span(),
             "You need to specify sub_description if sub is specified",
         )
         .to_compile_error()
         .into();
     }
 
+    if let (Some(group), None) = (&config.group, &config.group_description) {
+        return syn::Error::new(
+            group.span(),
+            "You need to specify group_description if group is specified",
+        )
+        .to_compile_error()
+        .into();
+    }
+
     match generate_command_controller(impl_, config) {
         Ok(t) => t.into(),
         Err(e) => e.write_errors().into(),
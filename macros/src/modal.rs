@@ -0,0 +1,150 @@
+use crate::generate::get_inner_optional;
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+
+#[derive(Debug, FromField)]
+#[darling(attributes(text_input))]
+struct TextInputInfo {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    label: Option<String>,
+    style: Option<String>,
+    placeholder: Option<String>,
+    value: Option<String>,
+    min_length: Option<u16>,
+    max_length: Option<u16>,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(form), supports(struct_named))]
+struct ModalInfo {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<(), TextInputInfo>,
+    title: String,
+}
+
+pub(crate) fn generate_modal(
+    input: syn::DeriveInput,
+) -> Result<TokenStream, Box<dyn crate::Error>> {
+    let info = match ModalInfo::from_derive_input(&input) {
+        Ok(i) => i,
+        Err(e) => return Err(Box::new(e)),
+    };
+
+    let fields = info.data.take_struct().unwrap().fields;
+    if fields.len() > 5 {
+        return Err(Box::new(syn::Error::new(
+            input.span(),
+            "A modal can not have more than 5 text inputs",
+        )));
+    }
+
+    let mut components = vec![];
+    let mut parsers = vec![];
+    for field in fields {
+        let ident = field.ident.unwrap();
+        let custom_id = ident.to_string();
+        let label = field.label.unwrap_or_else(|| custom_id.clone());
+
+        let style = match field.style.as_deref() {
+            None | Some("short") => quote! { Short },
+            Some("paragraph") => quote! { Paragraph },
+            Some(_) => {
+                return Err(Box::new(syn::Error::new(
+                    field.ty.span(),
+                    "The style has to be either \"short\" or \"paragraph\"",
+                )))
+            }
+        };
+
+        let placeholder = match field.placeholder {
+            Some(p) => quote! { Some(#p.to_string()) },
+            None => quote! { None },
+        };
+        let value = match field.value {
+            Some(v) => quote! { Some(#v.to_string()) },
+            None => quote! { None },
+        };
+        let min_length = match field.min_length {
+            Some(l) => quote! { Some(#l) },
+            None => quote! { None },
+        };
+        let max_length = match field.max_length {
+            Some(l) => quote! { Some(#l) },
+            None => quote! { None },
+        };
+
+        let is_optional = get_inner_optional(&field.ty).is_some();
+        let required = !is_optional;
+
+        components.push(quote! {
+            ::nightfall::export::twilight_model::channel::message::Component::ActionRow(
+                ::nightfall::export::twilight_model::channel::message::component::ActionRow {
+                    components: vec![
+                        ::nightfall::export::twilight_model::channel::message::Component::TextInput(
+                            ::nightfall::export::twilight_model::channel::message::component::TextInput {
+                                custom_id: #custom_id.to_string(),
+                                label: #label.to_string(),
+                                max_length: #max_length,
+                                min_length: #min_length,
+                                placeholder: #placeholder,
+                                required: Some(#required),
+                                style: ::nightfall::export::twilight_model::channel::message::component::TextInputStyle::#style,
+                                value: #value,
+                            }
+                        )
+                    ],
+                }
+            )
+        });
+
+        // Discord sends an empty string for optional inputs the user left empty
+        let parser = if is_optional {
+            quote! {
+                match ::nightfall::modal::get_text_input_value(data, #custom_id) {
+                    Some(v) if !v.is_empty() => Some(v.parse().ok()?),
+                    _ => None,
+                }
+            }
+        } else {
+            quote! {
+                ::nightfall::modal::get_text_input_value(data, #custom_id)?.parse().ok()?
+            }
+        };
+        parsers.push(quote! { #ident: #parser });
+    }
+
+    let ident = &info.ident;
+    let title = &info.title;
+    let (impl_generics, ty_generics, where_clause) = info.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::nightfall::modal::Modal for #ident #ty_generics #where_clause {
+            fn create_modal(
+                custom_id: &str,
+            ) -> ::nightfall::export::twilight_model::http::interaction::InteractionResponse {
+                ::nightfall::export::twilight_model::http::interaction::InteractionResponse {
+                    kind: ::nightfall::export::twilight_model::http::interaction::InteractionResponseType::Modal,
+                    data: Some(
+                        ::nightfall::export::twilight_util::builder::InteractionResponseDataBuilder::new()
+                            .custom_id(custom_id)
+                            .title(#title)
+                            .components([#(#components),*])
+                            .build(),
+                    ),
+                }
+            }
+
+            fn parse_modal(
+                data: &::nightfall::export::twilight_model::application::interaction::modal::ModalInteractionData,
+            ) -> Option<Self> {
+                Some(Self {
+                    #(#parsers),*
+                })
+            }
+        }
+    })
+}
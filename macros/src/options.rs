@@ -0,0 +1,128 @@
+use crate::generate::{
+    generate_constraints, generate_create_option, generate_option_binding,
+    generate_option_localizations,
+};
+use crate::validate::{check_option, check_options, resolve_description, value_span};
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+
+#[derive(Debug, FromField)]
+#[darling(attributes(option), forward_attrs(doc))]
+struct OptionFieldInfo {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
+    #[darling(flatten)]
+    option: crate::OptionInfo,
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(command_options), supports(struct_named))]
+struct CommandOptionsInfo {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: darling::ast::Data<(), OptionFieldInfo>,
+    #[darling(default)]
+    truncate_descriptions: bool,
+}
+
+pub(crate) fn generate_command_options(
+    input: syn::DeriveInput,
+) -> Result<TokenStream, Box<dyn crate::Error>> {
+    let info = match CommandOptionsInfo::from_derive_input(&input) {
+        Ok(i) => i,
+        Err(e) => return Err(Box::new(e)),
+    };
+
+    let fields = info.data.take_struct().unwrap().fields;
+
+    let mut options = vec![];
+    let mut option_names = vec![];
+    let mut localizations = vec![];
+    let mut bindings = vec![];
+    for field in fields {
+        let ident = field.ident.unwrap();
+        let option = &field.option;
+
+        if let Some(autocomplete) = &option.autocomplete {
+            return Err(Box::new(syn::Error::new(
+                autocomplete.span(),
+                "Autocomplete handlers can only be set on options of a command",
+            )));
+        }
+
+        let name = option
+            .name
+            .as_deref()
+            .cloned()
+            .unwrap_or_else(|| ident.to_string());
+        let name_span = value_span(option.name.as_ref(), ident.span());
+        check_option(option, &name, name_span)?;
+        let description = resolve_description(
+            option.description.as_deref(),
+            crate::generate::get_doc_comment(&field.attrs),
+            info.truncate_descriptions,
+            value_span(option.description.as_ref(), ident.span()),
+        )?;
+        option_names.push((
+            name.clone(),
+            crate::generate::get_inner_optional(&field.ty).is_none(),
+            name_span,
+        ));
+
+        options.push(generate_create_option(
+            option,
+            &name,
+            &description,
+            &field.ty,
+        ));
+        localizations.push(generate_option_localizations(
+            option,
+            &field.ty,
+            &quote! {
+                {
+                    let mut path = path.to_vec();
+                    path.push(#name);
+                    path
+                }
+            },
+        ));
+
+        let constraints = generate_constraints(option);
+        let binding =
+            generate_option_binding(&quote! { options }, &name, &field.ty, constraints.as_ref());
+        bindings.push(quote! { #ident: #binding });
+    }
+
+    check_options(&option_names, input.span())?;
+
+    let ident = &info.ident;
+    let (impl_generics, ty_generics, where_clause) = info.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::nightfall::CommandOptions for #ident #ty_generics #where_clause {
+            fn create_options() -> Vec<::nightfall::export::twilight_model::application::command::CommandOption> {
+                vec![#(#options),*]
+            }
+
+            fn localization_keys(
+                path: &[&'static str],
+            ) -> Vec<::nightfall::localization::LocalizationKey> {
+                ::std::iter::empty()
+                    #(.chain(#localizations))*
+                    .collect()
+            }
+
+            fn from_options(
+                options: &[::nightfall::export::twilight_model::application::interaction::application_command::CommandDataOption],
+                data: &::nightfall::export::twilight_model::application::interaction::application_command::CommandData,
+            ) -> Result<Self, ::nightfall::Error> {
+                Ok(Self {
+                    #(#bindings),*
+                })
+            }
+        }
+    })
+}
@@ -0,0 +1,431 @@
+use darling::util::SpannedValue;
+use proc_macro2::Span;
+use std::fmt::Display;
+use syn::spanned::Spanned;
+
+/// Discord's limit for the names, descriptions and choices of a command, its
+/// sub commands and options combined
+const MAX_COMMAND_LENGTH: usize = 4000;
+
+fn error(span: Span, message: impl Display) -> Box<dyn crate::Error> {
+    Box::new(syn::Error::new(span, message))
+}
+
+/// The span of a value set in an attribute, or of what it defaults to
+pub(crate) fn value_span<T>(value: Option<&SpannedValue<T>>, fallback: Span) -> Span {
+    value.map_or(fallback, |v| v.span())
+}
+
+fn is_name_char(c: char) -> bool {
+    // Devanagari and Thai contain marks that are neither letters nor numbers
+    (c.is_alphanumeric() && !c.is_uppercase())
+        || c == '-'
+        || c == '_'
+        || ('\u{0900}'..='\u{097f}').contains(&c)
+        || ('\u{0e00}'..='\u{0e7f}').contains(&c)
+}
+
+/// Checks the name of a chat input command, sub command group, sub command or
+/// option
+pub(crate) fn check_name(name: &str, span: Span) -> Result<(), Box<dyn crate::Error>> {
+    let length = name.chars().count();
+    if !(1..=32).contains(&length) {
+        return Err(error(
+            span,
+            format!(
+                "The name `{}` has to be between 1 and 32 characters long",
+                name
+            ),
+        ));
+    }
+
+    if let Some(c) = name.chars().find(|c| !is_name_char(*c)) {
+        return Err(error(
+            span,
+            format!(
+                "The name `{}` can not contain `{}`, only lowercase letters, numbers, `-` and `_` are allowed",
+                name, c
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the name of a user or message command, which can contain spaces and
+/// uppercase letters
+pub(crate) fn check_context_menu_name(name: &str, span: Span) -> Result<(), Box<dyn crate::Error>> {
+    let length = name.chars().count();
+    if !(1..=32).contains(&length) {
+        return Err(error(
+            span,
+            format!(
+                "The name `{}` has to be between 1 and 32 characters long",
+                name
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_description(
+    description: &str,
+    span: Span,
+) -> Result<(), Box<dyn crate::Error>> {
+    let length = description.chars().count();
+    if length == 0 {
+        return Err(error(span, "The description can not be empty"));
+    }
+    if length > 100 {
+        return Err(error(
+            span,
+            format!(
+                "The description can not be longer than 100 characters but is {} characters long",
+                length
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The description set in the attribute or else the doc comment, a
+/// description that is too long is cut off if `truncate` is set
+pub(crate) fn resolve_description(
+    description: Option<&String>,
+    doc_comment: Option<String>,
+    truncate: bool,
+    span: Span,
+) -> Result<String, Box<dyn crate::Error>> {
+    let Some(mut description) = description.cloned().or(doc_comment) else {
+        return Err(error(
+            span,
+            "A description is required, either as `description` or as a doc comment",
+        ));
+    };
+
+    if description.chars().count() > 100 {
+        if !truncate {
+            return Err(error(
+                span,
+                "The description is longer than 100 characters, shorten it or set `truncate_descriptions` to cut it off",
+            ));
+        }
+
+        description = description.chars().take(99).chain(['…']).collect();
+    }
+
+    check_description(&description, span)?;
+    Ok(description)
+}
+
+pub(crate) fn check_choice(
+    name: &str,
+    value: Option<&syn::Lit>,
+    span: Span,
+) -> Result<(), Box<dyn crate::Error>> {
+    let length = name.chars().count();
+    if !(1..=100).contains(&length) {
+        return Err(error(
+            span,
+            format!(
+                "The choice name `{}` has to be between 1 and 100 characters long",
+                name
+            ),
+        ));
+    }
+
+    if let Some(syn::Lit::Str(value)) = value {
+        if value.value().chars().count() > 100 {
+            return Err(error(
+                value.span(),
+                "A choice value can not be longer than 100 characters",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The value of an integer or float literal, constants can't be checked
+fn literal_value(expr: &syn::Expr) -> Option<f64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(i),
+            ..
+        }) => i.base10_parse().ok(),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Float(f),
+            ..
+        }) => f.base10_parse().ok(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => literal_value(expr).map(|v| -v),
+        syn::Expr::Paren(p) => literal_value(&p.expr),
+        _ => None,
+    }
+}
+
+/// Checks the name, choices and length limits of an option, `span` points at
+/// its name
+pub(crate) fn check_option(
+    option: &crate::OptionInfo,
+    name: &str,
+    span: Span,
+) -> Result<(), Box<dyn crate::Error>> {
+    check_name(name, span)?;
+
+    if option.choices.len() > 25 {
+        return Err(error(span, "An option can not have more than 25 choices"));
+    }
+    for choice in &option.choices {
+        check_choice(&choice.name, Some(&choice.value), choice.name.span())?;
+    }
+
+    if let (Some(min), Some(max)) = (&option.min_value, &option.max_value) {
+        if let (Some(min_value), Some(max_value)) = (literal_value(min), literal_value(max)) {
+            if min_value > max_value {
+                return Err(error(
+                    min.span(),
+                    "min_value can not be greater than max_value",
+                ));
+            }
+        }
+    }
+
+    if let Some(min) = option.min_length.as_ref().filter(|l| ***l > 6000) {
+        return Err(error(min.span(), "min_length can not be greater than 6000"));
+    }
+    if let Some(max) = option
+        .max_length
+        .as_ref()
+        .filter(|l| !(1..=6000).contains(&***l))
+    {
+        return Err(error(max.span(), "max_length has to be between 1 and 6000"));
+    }
+    if let (Some(min), Some(max)) = (&option.min_length, &option.max_length) {
+        if **min > **max {
+            return Err(error(
+                min.span(),
+                "min_length can not be greater than max_length",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the options of a command as the name, whether the option is
+/// required and the span of the name, `span` points at the command
+pub(crate) fn check_options(
+    options: &[(String, bool, Span)],
+    span: Span,
+) -> Result<(), Box<dyn crate::Error>> {
+    if options.len() > 25 {
+        return Err(error(span, "A command can not have more than 25 options"));
+    }
+
+    for (i, (name, required, name_span)) in options.iter().enumerate() {
+        if options[..i].iter().any(|(n, _, _)| n == name) {
+            return Err(error(
+                *name_span,
+                format!("There is more than one option named `{}`", name),
+            ));
+        }
+
+        if *required && options[..i].iter().any(|(_, r, _)| !r) {
+            return Err(error(
+                *name_span,
+                format!(
+                    "The required option `{}` has to come before all optional options",
+                    name
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// How many characters of a command an option takes up, choices that come
+/// from the option's type aren't known to the macro
+pub(crate) fn option_length(option: &crate::OptionInfo, name: &str, description: &str) -> usize {
+    let choices: usize = option
+        .choices
+        .iter()
+        .map(|c| {
+            let value = match &c.value {
+                syn::Lit::Str(s) => s.value().chars().count(),
+                syn::Lit::Int(i) => i.base10_digits().len(),
+                syn::Lit::Float(f) => f.base10_digits().len(),
+                _ => 0,
+            };
+            c.name.chars().count() + value
+        })
+        .sum();
+
+    name.chars().count() + description.chars().count() + choices
+}
+
+pub(crate) fn check_command_length(
+    name: &str,
+    length: usize,
+    span: Span,
+) -> Result<(), Box<dyn crate::Error>> {
+    if length > MAX_COMMAND_LENGTH {
+        return Err(error(
+            span,
+            format!(
+                "The names, descriptions and choices of `{}` can not be longer than {} characters combined but are {} characters long",
+                name, MAX_COMMAND_LENGTH, length
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the sub commands of a command or sub command group
+pub(crate) fn check_sub_commands(
+    names: &[String],
+    span: Span,
+) -> Result<(), Box<dyn crate::Error>> {
+    if names.len() > 25 {
+        return Err(error(
+            span,
+            "A command or group can not have more than 25 sub commands",
+        ));
+    }
+
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(error(
+                span,
+                format!("There is more than one sub command named `{}`", name),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darling::FromMeta;
+
+    fn span() -> Span {
+        Span::call_site()
+    }
+
+    fn option(meta: syn::Meta) -> crate::OptionInfo {
+        crate::OptionInfo::from_meta(&meta).unwrap()
+    }
+
+    fn options(options: &[(&str, bool)]) -> Vec<(String, bool, Span)> {
+        options
+            .iter()
+            .map(|(n, r)| (n.to_string(), *r, span()))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_valid_names() {
+        assert!(check_name("ban", span()).is_ok());
+        assert!(check_name("warn-add_2", span()).is_ok());
+        assert!(check_name("नमस्ते", span()).is_ok());
+    }
+
+    #[test]
+    fn rejects_uppercase_names() {
+        assert!(check_name("Ban", span()).is_err());
+        assert!(check_name("ban user", span()).is_err());
+    }
+
+    #[test]
+    fn rejects_names_by_length() {
+        assert!(check_name("", span()).is_err());
+        assert!(check_name(&"a".repeat(32), span()).is_ok());
+        assert!(check_name(&"a".repeat(33), span()).is_err());
+        assert!(check_context_menu_name("Ban User", span()).is_ok());
+        assert!(check_context_menu_name(&"A".repeat(33), span()).is_err());
+    }
+
+    #[test]
+    fn rejects_descriptions_by_length() {
+        assert!(check_description("", span()).is_err());
+        assert!(check_description(&"d".repeat(100), span()).is_ok());
+        assert!(check_description(&"d".repeat(101), span()).is_err());
+    }
+
+    #[test]
+    fn truncates_descriptions() {
+        let long = "d".repeat(120);
+
+        assert!(resolve_description(Some(&long), None, false, span()).is_err());
+        let Ok(description) = resolve_description(Some(&long), None, true, span()) else {
+            panic!("the description wasn't truncated");
+        };
+        assert_eq!(description.chars().count(), 100);
+        assert!(description.ends_with('…'));
+        assert!(resolve_description(None, None, false, span()).is_err());
+    }
+
+    #[test]
+    fn rejects_more_than_25_options() {
+        let names = (0..26).map(|i| format!("o{}", i)).collect::<Vec<_>>();
+        let many = names.iter().map(|n| (n.as_str(), true)).collect::<Vec<_>>();
+
+        assert!(check_options(&options(&many[..25]), span()).is_ok());
+        assert!(check_options(&options(&many), span()).is_err());
+    }
+
+    #[test]
+    fn rejects_required_after_optional() {
+        assert!(check_options(&options(&[("a", true), ("b", false)]), span()).is_ok());
+        assert!(check_options(&options(&[("a", false), ("b", true)]), span()).is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_options() {
+        assert!(check_options(&options(&[("a", true), ("a", false)]), span()).is_err());
+    }
+
+    #[test]
+    fn rejects_inverted_ranges() {
+        let valid = option(syn::parse_quote!(option(min_value = -5, max_value = 5)));
+        let values = option(syn::parse_quote!(option(min_value = 5, max_value = -5)));
+        let lengths = option(syn::parse_quote!(option(min_length = 10, max_length = 5)));
+
+        assert!(check_option(&valid, "n", span()).is_ok());
+        assert!(check_option(&values, "n", span()).is_err());
+        assert!(check_option(&lengths, "n", span()).is_err());
+    }
+
+    #[test]
+    fn rejects_more_than_25_choices() {
+        let choices = |count: usize| -> crate::OptionInfo {
+            let choices = (0..count).map(|i| {
+                let name = format!("c{}", i);
+                let value = proc_macro2::Literal::usize_unsuffixed(i);
+                quote::quote! { choice(name = #name, value = #value) }
+            });
+            option(syn::parse_quote!(option(#(#choices),*)))
+        };
+
+        assert!(check_option(&choices(25), "n", span()).is_ok());
+        assert!(check_option(&choices(26), "n", span()).is_err());
+    }
+
+    #[test]
+    fn counts_the_combined_length() {
+        let option = option(syn::parse_quote!(option(choice(name = "one", value = 1))));
+
+        assert_eq!(option_length(&option, "n", "desc"), 9);
+        assert!(check_command_length("c", 4000, span()).is_ok());
+        assert!(check_command_length("c", 4001, span()).is_err());
+    }
+}
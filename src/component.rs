@@ -0,0 +1,87 @@
+/// Matches a custom id against a pattern such as `ticket:{id}:close` and
+/// returns the values of the placeholders in the order they appear in.
+///
+/// A placeholder captures everything up until the next literal part of the
+/// pattern and has to capture at least one character.
+pub fn match_custom_id<'a>(pattern: &str, custom_id: &'a str) -> Option<Vec<&'a str>> {
+    let mut segments = vec![];
+    let mut pattern = pattern;
+    let mut rest = custom_id;
+
+    while !pattern.is_empty() {
+        if let Some(placeholder) = pattern.strip_prefix('{') {
+            let end = placeholder.find('}')?;
+            pattern = &placeholder[end + 1..];
+
+            let literal_end = pattern.find('{').unwrap_or(pattern.len());
+            let literal = &pattern[..literal_end];
+            let value_end = if literal.is_empty() {
+                rest.len()
+            } else {
+                rest.find(literal)?
+            };
+
+            if value_end == 0 {
+                return None;
+            }
+
+            segments.push(&rest[..value_end]);
+            rest = &rest[value_end..];
+        } else {
+            let literal_end = pattern.find('{').unwrap_or(pattern.len());
+            rest = rest.strip_prefix(&pattern[..literal_end])?;
+            pattern = &pattern[literal_end..];
+        }
+    }
+
+    rest.is_empty().then_some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_literal_patterns() {
+        assert_eq!(match_custom_id("close", "close"), Some(vec![]));
+        assert_eq!(match_custom_id("close", "open"), None);
+    }
+
+    #[test]
+    fn captures_placeholders() {
+        assert_eq!(
+            match_custom_id("ticket:{id}:close", "ticket:42:close"),
+            Some(vec!["42"])
+        );
+        assert_eq!(
+            match_custom_id("{kind}-{id}", "poll-7"),
+            Some(vec!["poll", "7"])
+        );
+        assert_eq!(match_custom_id("page:{n}", "page:10"), Some(vec!["10"]));
+    }
+
+    #[test]
+    fn placeholders_capture_up_to_the_next_literal() {
+        assert_eq!(match_custom_id("{a}:{b}", "x:y:z"), Some(vec!["x", "y:z"]));
+    }
+
+    #[test]
+    fn rejects_partial_matches() {
+        assert_eq!(match_custom_id("ticket:{id}:close", "ticket:42"), None);
+        assert_eq!(match_custom_id("ticket:{id}", "ticket:"), None);
+        assert_eq!(match_custom_id("ticket:{id}:close", "ticket::close"), None);
+    }
+
+    #[test]
+    fn rejects_extra_segments() {
+        assert_eq!(match_custom_id("close", "close:now"), None);
+        assert_eq!(
+            match_custom_id("ticket:{id}:close", "ticket:42:close:now"),
+            None
+        );
+        assert_eq!(
+            match_custom_id("ticket:{id}:close", "old:ticket:42:close"),
+            None
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::response::{
+    self, Acknowledgement, AcknowledgementState, Defer, IntoResponse, Reply, Responder,
+};
+use crate::Error;
+use deppy::{Dep, ServiceHandler};
+use std::error::Error as ErrorTrait;
+use std::fmt;
+use std::ops::Deref;
+use twilight_http::Client;
+use twilight_model::channel::message::MessageFlags;
+use twilight_model::gateway::payload::incoming::InteractionCreate;
+use twilight_model::id::marker::ApplicationMarker;
+use twilight_model::id::Id;
+
+fn failed(error: impl ErrorTrait + Send + Sync + 'static) -> Error {
+    Error::ResponseFailed {
+        error: Box::new(error),
+    }
+}
+
+/// Responds to the interaction it was created for with the HTTP client from
+/// the services.
+///
+/// Whether the interaction was already responded to is shared with the
+/// framework, so the helpers pick the right endpoint and responding twice
+/// fails with [`Error::AlreadyAcknowledged`].
+pub struct Context {
+    interaction: InteractionCreate,
+    client: Dep<Client>,
+    acknowledgement: Acknowledgement,
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("interaction", &self.interaction)
+            .field("acknowledgement", &self.acknowledgement)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Context {
+    /// Creates the context, `None` if the HTTP client isn't a service which
+    /// handlers report as [`Error::ContextUnavailable`]
+    pub fn new<S: ServiceHandler>(
+        interaction: &InteractionCreate,
+        services: &S,
+        acknowledgement: &Acknowledgement,
+    ) -> Option<Self> {
+        Some(Self {
+            interaction: interaction.clone(),
+            client: services.get_service::<Client>()?,
+            acknowledgement: acknowledgement.clone(),
+        })
+    }
+
+    pub fn interaction(&self) -> &InteractionCreate {
+        &self.interaction
+    }
+
+    pub fn application_id(&self) -> Id<ApplicationMarker> {
+        self.interaction.application_id
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn state(&self) -> AcknowledgementState {
+        self.acknowledgement.state()
+    }
+
+    /// Sends the initial response, a message replaces the loading message if
+    /// the interaction was deferred and is ephemeral if the command is
+    pub async fn reply(&self, response: impl IntoResponse) -> Result<(), Error> {
+        let Some(response) = response.into_response() else {
+            return Ok(());
+        };
+
+        response::send(
+            self.client.deref(),
+            &self.interaction,
+            &self.acknowledgement,
+            response,
+        )
+        .await
+    }
+
+    pub async fn reply_ephemeral(&self, content: impl Into<String>) -> Result<(), Error> {
+        self.reply(Reply::new().content(content).ephemeral()).await
+    }
+
+    /// Acknowledges the interaction so the reply can be sent later
+    pub async fn defer(&self) -> Result<(), Error> {
+        self.reply(Defer::new()).await
+    }
+
+    /// Sends another message after the interaction was responded to, which
+    /// is ephemeral if the command is
+    pub async fn followup(&self, response: impl IntoResponse) -> Result<(), Error> {
+        self.require_acknowledged()?;
+        let Some(data) = response.into_response().and_then(|r| r.data) else {
+            return Ok(());
+        };
+
+        let client = self.client.interaction(self.interaction.application_id);
+        let mut request = client.create_followup(&self.interaction.token);
+        if let Some(content) = &data.content {
+            request = request.content(content).map_err(failed)?;
+        }
+        if let Some(embeds) = &data.embeds {
+            request = request.embeds(embeds).map_err(failed)?;
+        }
+        if let Some(components) = &data.components {
+            request = request.components(components).map_err(failed)?;
+        }
+        let flags = if self.acknowledgement.is_ephemeral() {
+            Some(data.flags.unwrap_or(MessageFlags::empty()) | MessageFlags::EPHEMERAL)
+        } else {
+            data.flags
+        };
+        if let Some(flags) = flags {
+            request = request.flags(flags);
+        }
+
+        request.await.map_err(failed)?;
+        Ok(())
+    }
+
+    /// Edits the message of the initial response
+    pub async fn edit_original(&self, response: impl IntoResponse) -> Result<(), Error> {
+        self.require_acknowledged()?;
+        let Some(data) = response.into_response().and_then(|r| r.data) else {
+            return Ok(());
+        };
+
+        match Responder::edit_original(self.client.deref(), &self.interaction, &data).await {
+            Ok(()) => Ok(()),
+            Err(e) => Err(Error::ResponseFailed { error: e }),
+        }
+    }
+
+    pub async fn delete_original(&self) -> Result<(), Error> {
+        self.require_acknowledged()?;
+
+        let client = self.client.interaction(self.interaction.application_id);
+        client
+            .delete_response(&self.interaction.token)
+            .await
+            .map_err(failed)?;
+
+        Ok(())
+    }
+
+    fn require_acknowledged(&self) -> Result<(), Error> {
+        if self.acknowledgement.state() == AcknowledgementState::Pending {
+            return Err(Error::NotAcknowledged);
+        }
+
+        Ok(())
+    }
+}
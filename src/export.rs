@@ -1,3 +1,4 @@
 pub use async_trait;
+pub use deppy;
 pub use twilight_model;
-pub use twilight_util;
\ No newline at end of file
+pub use twilight_util;
@@ -1,41 +1,152 @@
+pub mod component;
+#[cfg(feature = "services")]
+pub mod context;
 pub mod export;
+pub mod localization;
+pub mod modal;
 pub mod register;
+pub mod response;
 #[cfg(feature = "services")]
 pub mod services;
+pub mod spec;
+
+#[cfg(feature = "services")]
+pub use context::Context;
 
 use async_trait::async_trait;
-use deppy::ServiceHandler;
+use deppy::{Dep, ServiceHandler};
+use localization::{LocalizationKey, Localizations, MissingLocalization};
+use response::{Acknowledgement, AcknowledgementState, Defer, IntoResponse, Responder};
 use snafu::Snafu;
+use spec::{CommandContext, CommandSpec};
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error as ErrorTrait;
+use std::fmt;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
 use std::sync::Arc;
-use twilight_model::application::command::{Command, CommandOptionType};
+use std::time::Duration;
+use twilight_model::application::command::{
+    Command, CommandOption, CommandOptionChoice, CommandOptionType, CommandType,
+};
 use twilight_model::application::interaction::application_command::{
     CommandData, CommandDataOption, CommandOptionValue,
 };
-use twilight_model::application::interaction::InteractionData;
+use twilight_model::application::interaction::message_component::MessageComponentInteractionData;
+use twilight_model::application::interaction::modal::ModalInteractionData;
+use twilight_model::application::interaction::{
+    InteractionChannel, InteractionData, InteractionMember, InteractionType,
+};
+use twilight_model::channel::{Attachment, Message};
 use twilight_model::gateway::payload::incoming::InteractionCreate;
+use twilight_model::guild::{Permissions, Role};
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseType};
 use twilight_model::id::marker::{
-    AttachmentMarker, ChannelMarker, GenericMarker, RoleMarker, UserMarker,
+    AttachmentMarker, ChannelMarker, GenericMarker, GuildMarker, MessageMarker, RoleMarker,
+    UserMarker,
 };
 use twilight_model::id::Id;
+use twilight_model::user::User;
+use twilight_util::builder::InteractionResponseDataBuilder;
 
+/// A controller that handles interactions, `S` is the scope of the service
+/// handler that services are injected from.
+///
+/// Handlers return the response that should be sent for the interaction, if
+/// they didn't respond by themselves through the `acknowledgement`.
 #[async_trait]
-pub trait CommandController {
+pub trait CommandController<S: ServiceHandler + Sync>: ControllerMetadata + Send + Sync {
     async fn execute_command(
         &self,
         interaction: &InteractionCreate,
         data: &CommandData,
-    ) -> Result<(), Error>;
+        services: &S,
+        acknowledgement: &Acknowledgement,
+    ) -> Result<Option<InteractionResponse>, Error>;
+
+    async fn execute_autocomplete(
+        &self,
+        _interaction: &InteractionCreate,
+        _data: &CommandData,
+        _services: &S,
+    ) -> Result<Vec<CommandOptionChoice>, Error> {
+        Err(Error::CommandNotFound)
+    }
+
+    async fn execute_component(
+        &self,
+        _interaction: &InteractionCreate,
+        _data: &MessageComponentInteractionData,
+        _services: &S,
+        _acknowledgement: &Acknowledgement,
+    ) -> Result<Option<InteractionResponse>, Error> {
+        Err(Error::ComponentNotFound)
+    }
+
+    async fn execute_modal(
+        &self,
+        _interaction: &InteractionCreate,
+        _data: &ModalInteractionData,
+        _services: &S,
+        _acknowledgement: &Acknowledgement,
+    ) -> Result<Option<InteractionResponse>, Error> {
+        Err(Error::ModalNotFound)
+    }
+}
+
+/// What a controller registers, kept apart from [`CommandController`] so it
+/// can be read without naming a service scope
+pub trait ControllerMetadata {
+    /// The routes of commands that set `auto_defer` themselves, with whether
+    /// they are deferred
+    fn get_auto_defer_commands<'a>() -> &'a [(&'static str, bool)]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// The routes of commands whose messages are only shown to the user,
+    /// context menus are routed by their name
+    fn get_ephemeral_commands<'a>() -> &'a [(CommandType, &'static str)]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// Whether the messages of the components and modals are only shown to
+    /// the user
+    fn is_ephemeral() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn get_component_patterns<'a>() -> &'a [&'static str]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
 
-    fn get_command_names<'a>() -> &'a [&'static str]
+    fn get_modal_patterns<'a>() -> &'a [&'static str]
     where
         Self: Sized,
     {
         &[]
     }
 
+    fn get_localization_keys() -> Vec<LocalizationKey>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
+
     fn build_commands() -> Vec<Command>
     where
         Self: Sized,
@@ -44,14 +155,16 @@ pub trait CommandController {
     }
 }
 
+/// Converts the value of an option, the command data is passed along for
+/// types that are filled from the resolved data of the interaction
 pub trait FromOption {
-    fn from_option(value: CommandOptionValue) -> Option<Self>
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self>
     where
         Self: Sized;
 }
 
 impl FromOption for Id<AttachmentMarker> {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Attachment(v) = value {
             Some(v)
         } else {
@@ -61,7 +174,7 @@ impl FromOption for Id<AttachmentMarker> {
 }
 
 impl FromOption for bool {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Boolean(v) = value {
             Some(v)
         } else {
@@ -71,7 +184,7 @@ impl FromOption for bool {
 }
 
 impl FromOption for Id<ChannelMarker> {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Channel(v) = value {
             Some(v)
         } else {
@@ -81,7 +194,7 @@ impl FromOption for Id<ChannelMarker> {
 }
 
 impl FromOption for (String, CommandOptionType) {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Focused(v, v2) = value {
             Some((v, v2))
         } else {
@@ -91,7 +204,7 @@ impl FromOption for (String, CommandOptionType) {
 }
 
 impl FromOption for i64 {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Integer(v) = value {
             Some(v)
         } else {
@@ -100,8 +213,24 @@ impl FromOption for i64 {
     }
 }
 
+macro_rules! impl_from_option_for_integer {
+    ($($ty:ty),* $(,)?) => {$(
+        impl FromOption for $ty {
+            fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
+                if let CommandOptionValue::Integer(v) = value {
+                    <$ty>::try_from(v).ok()
+                } else {
+                    None
+                }
+            }
+        }
+    )*};
+}
+
+impl_from_option_for_integer!(i8, i16, i32, u8, u16, u32, u64, usize);
+
 impl FromOption for Id<GenericMarker> {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Mentionable(v) = value {
             Some(v)
         } else {
@@ -111,7 +240,7 @@ impl FromOption for Id<GenericMarker> {
 }
 
 impl FromOption for f64 {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Number(v) = value {
             Some(v)
         } else {
@@ -121,7 +250,7 @@ impl FromOption for f64 {
 }
 
 impl FromOption for Id<RoleMarker> {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::Role(v) = value {
             Some(v)
         } else {
@@ -131,7 +260,7 @@ impl FromOption for Id<RoleMarker> {
 }
 
 impl FromOption for String {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::String(v) = value {
             Some(v)
         } else {
@@ -141,7 +270,7 @@ impl FromOption for String {
 }
 
 impl FromOption for Vec<CommandDataOption> {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         match value {
             CommandOptionValue::SubCommand(v) => Some(v),
             CommandOptionValue::SubCommandGroup(v) => Some(v),
@@ -151,7 +280,7 @@ impl FromOption for Vec<CommandDataOption> {
 }
 
 impl FromOption for Id<UserMarker> {
-    fn from_option(value: CommandOptionValue) -> Option<Self> {
+    fn from_option(value: CommandOptionValue, _: &CommandData) -> Option<Self> {
         if let CommandOptionValue::User(v) = value {
             Some(v)
         } else {
@@ -160,69 +289,1059 @@ impl FromOption for Id<UserMarker> {
     }
 }
 
+impl FromOption for User {
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self> {
+        let CommandOptionValue::User(id) = value else {
+            return None;
+        };
+
+        data.resolved.as_ref()?.users.get(&id).cloned()
+    }
+}
+
+impl FromOption for ResolvedMember {
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self> {
+        let CommandOptionValue::User(id) = value else {
+            return None;
+        };
+
+        ResolvedMember::resolve(id, data)
+    }
+}
+
+impl FromOption for Role {
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self> {
+        let CommandOptionValue::Role(id) = value else {
+            return None;
+        };
+
+        data.resolved.as_ref()?.roles.get(&id).cloned()
+    }
+}
+
+impl FromOption for InteractionChannel {
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self> {
+        let CommandOptionValue::Channel(id) = value else {
+            return None;
+        };
+
+        data.resolved.as_ref()?.channels.get(&id).cloned()
+    }
+}
+
+impl FromOption for Attachment {
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self> {
+        let CommandOptionValue::Attachment(id) = value else {
+            return None;
+        };
+
+        data.resolved.as_ref()?.attachments.get(&id).cloned()
+    }
+}
+
+impl<T: FromOption> FromOption for Option<T> {
+    fn from_option(value: CommandOptionValue, data: &CommandData) -> Option<Self> {
+        T::from_option(value, data).map(Some)
+    }
+}
+
+/// A member of the guild along with their user, both taken from the resolved
+/// data of the interaction
+#[derive(Debug, Clone)]
+pub struct ResolvedMember {
+    pub user: User,
+    pub member: InteractionMember,
+}
+
+impl ResolvedMember {
+    fn resolve(id: Id<UserMarker>, data: &CommandData) -> Option<Self> {
+        let resolved = data.resolved.as_ref()?;
+
+        Some(ResolvedMember {
+            user: resolved.users.get(&id)?.clone(),
+            member: resolved.members.get(&id)?.clone(),
+        })
+    }
+}
+
+pub trait FromTarget {
+    fn from_target(data: &CommandData) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl FromTarget for Id<UserMarker> {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        data.target_id.map(|t| t.cast())
+    }
+}
+
+impl FromTarget for User {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        let target = data.target_id?.cast();
+        data.resolved.as_ref()?.users.get(&target).cloned()
+    }
+}
+
+impl FromTarget for InteractionMember {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        let target = data.target_id?.cast();
+        data.resolved.as_ref()?.members.get(&target).cloned()
+    }
+}
+
+impl FromTarget for ResolvedMember {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        ResolvedMember::resolve(data.target_id?.cast(), data)
+    }
+}
+
+impl FromTarget for Id<MessageMarker> {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        data.target_id.map(|t| t.cast())
+    }
+}
+
+impl FromTarget for Message {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        let target = data.target_id?.cast();
+        data.resolved.as_ref()?.messages.get(&target).cloned()
+    }
+}
+
+impl<T: FromTarget> FromTarget for Option<T> {
+    fn from_target(data: &CommandData) -> Option<Self> {
+        Some(T::from_target(data))
+    }
+}
+
+/// Extracts a parameter marked with `#[extract]` from the interaction itself
+/// instead of from its options, it can be implemented for custom context types
+pub trait FromInteraction {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+/// The locale of the user that invoked the interaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale(pub String);
+
+impl FromInteraction for Id<GuildMarker> {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        interaction.guild_id
+    }
+}
+
+impl FromInteraction for Id<ChannelMarker> {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        interaction.channel.as_ref().map(|c| c.id)
+    }
+}
+
+impl FromInteraction for User {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        interaction.author().cloned()
+    }
+}
+
+impl FromInteraction for Locale {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        interaction.locale.clone().map(Locale)
+    }
+}
+
+impl FromInteraction for Permissions {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        interaction.app_permissions
+    }
+}
+
+impl FromInteraction for CommandData {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => Some(data.deref().clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromInteraction> FromInteraction for Option<T> {
+    fn from_interaction(interaction: &InteractionCreate) -> Option<Self> {
+        Some(T::from_interaction(interaction))
+    }
+}
+
+/// Resolves a parameter marked with `#[inject]` from the scope of the service
+/// handler the interaction is handled in
+pub trait Inject<S: ServiceHandler> {
+    fn inject(services: &S) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl<S: ServiceHandler, T: Any + Send + Sync> Inject<S> for Dep<T> {
+    fn inject(services: &S) -> Option<Self> {
+        services.get_service::<T>()
+    }
+}
+
+impl<S: ServiceHandler, T: Inject<S>> Inject<S> for Option<T> {
+    fn inject(services: &S) -> Option<Self> {
+        Some(T::inject(services))
+    }
+}
+
+/// A group of options that is registered and bound as a whole, a parameter
+/// marked with `#[options]` expands into all of them
+pub trait CommandOptions {
+    fn create_options() -> Vec<CommandOption>;
+
+    fn localization_keys(path: &[&'static str]) -> Vec<LocalizationKey>;
+
+    fn from_options(options: &[CommandDataOption], data: &CommandData) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
 #[derive(Debug, Snafu)]
 pub enum Error {
     #[snafu(display("The interaction is not an application command"))]
     NotApplicationCommand,
+    #[snafu(display("The interaction is not an autocomplete request"))]
+    NotAutocomplete,
     #[snafu(display("Could not find a command for the interaction"))]
     CommandNotFound,
-    #[snafu(display("Failed to bind options to the command"))]
-    OptionBindingFailed,
+    #[snafu(display("The command `{name}` is defined by more than one controller"))]
+    CommandConflict { name: String },
+    #[snafu(display("The options of the command `{command}` are invalid, {reason}"))]
+    InvalidOptions { command: String, reason: String },
+    #[snafu(display("The command or option `{path}` is given more than one localization key"))]
+    LocalizationConflict { path: String },
+    #[snafu(display("The interaction is not a message component"))]
+    NotMessageComponent,
+    #[snafu(display("Could not find a component handler for the interaction"))]
+    ComponentNotFound,
+    #[snafu(display("Failed to bind the custom id to the component handler"))]
+    ComponentBindingFailed,
+    #[snafu(display("The interaction is not a modal submit"))]
+    NotModalSubmit,
+    #[snafu(display("Could not find a modal handler for the interaction"))]
+    ModalNotFound,
+    #[snafu(display("Failed to bind the submitted modal to the modal handler"))]
+    ModalBindingFailed,
+    #[snafu(display(
+        "Failed to bind the option `{option}` of `/{command}`, {}",
+        describe_mismatch(expected, actual)
+    ))]
+    OptionBindingFailed {
+        command: String,
+        option: String,
+        expected: CommandOptionType,
+        actual: Option<CommandOptionType>,
+    },
+    #[snafu(display("Failed to bind the target of the command `{command}`"))]
+    TargetBindingFailed { command: String },
+    #[snafu(display("Failed to extract a parameter from the interaction"))]
+    ExtractionFailed,
+    #[snafu(display("Failed to resolve an injected service"))]
+    InjectionFailed,
+    #[snafu(display("A context requires the HTTP client to be a service"))]
+    ContextUnavailable,
     #[snafu(display("The command failed to execute"))]
-    CommandError { error: Box<dyn ErrorTrait> },
+    CommandError {
+        #[snafu(source)]
+        error: Box<dyn ErrorTrait + Send + Sync>,
+    },
+    #[snafu(display("A handler returned a response but no responder is configured"))]
+    NoResponder,
+    #[snafu(display("The interaction was already responded to"))]
+    AlreadyAcknowledged,
+    #[snafu(display("The interaction has to be responded to first"))]
+    NotAcknowledged,
+    #[snafu(display("Failed to send the response"))]
+    ResponseFailed {
+        #[snafu(source)]
+        error: Box<dyn ErrorTrait + Send + Sync>,
+    },
+}
+
+impl Error {
+    /// The error for an option of the invoked command that can't be bound,
+    /// `value` is `None` if the option is missing
+    pub fn option_binding(
+        data: &CommandData,
+        option: &str,
+        expected: CommandOptionType,
+        value: Option<&CommandOptionValue>,
+    ) -> Self {
+        Error::OptionBindingFailed {
+            command: command_route(data),
+            option: option.to_string(),
+            expected,
+            actual: value.map(CommandOptionValue::kind),
+        }
+    }
+}
+
+/// Handlers can return any error boxed, errors of the framework that were
+/// boxed on the way are unboxed again
+impl From<Box<dyn ErrorTrait + Send + Sync>> for Error {
+    fn from(error: Box<dyn ErrorTrait + Send + Sync>) -> Self {
+        match error.downcast::<Error>() {
+            Ok(e) => *e,
+            Err(e) => Error::CommandError { error: e },
+        }
+    }
+}
+
+fn describe_mismatch(expected: &CommandOptionType, actual: &Option<CommandOptionType>) -> String {
+    match actual {
+        None => "the option is missing".to_string(),
+        Some(actual) if actual != expected => {
+            format!("expected {:?} but got {:?}", expected, actual)
+        }
+        Some(_) => "the value isn't allowed".to_string(),
+    }
+}
+
+type Scope<T> = <T as ServiceHandler>::ScopeType;
+type ConvertFn<T> = fn(&Scope<T>) -> Arc<dyn CommandController<Scope<T>> + 'static>;
+type ErrorReplyFn = Arc<dyn Fn(&Error) -> Option<InteractionResponse> + Send + Sync>;
+type CommandFn<T> = Arc<
+    dyn Fn(
+            CommandContext<Scope<T>>,
+        )
+            -> Pin<Box<dyn Future<Output = Result<Option<InteractionResponse>, Error>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Leaves a second for the deferral to reach Discord before the interaction
+/// fails
+const DEFAULT_AUTO_DEFER_AFTER: Duration = Duration::from_secs(2);
+
+const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+/// What a chat input command is dispatched to
+enum CommandRoute<T: ServiceHandler> {
+    Controller(ConvertFn<T>),
+    Function(CommandFn<T>),
 }
 
-type ConvertFn<T> = fn(&<T as ServiceHandler>::ScopeType) -> Arc<dyn CommandController + 'static>; 
+impl<T: ServiceHandler> fmt::Debug for CommandRoute<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Controller(fn_) => f.debug_tuple("Controller").field(fn_).finish(),
+            Self::Function(_) => f.write_str("Function"),
+        }
+    }
+}
 
+/// A declaration that clashes with one of an earlier controller, reported by
+/// [`CommandHandler::build_commands`]
 #[derive(Debug)]
-pub struct CommandHandler<T: ServiceHandler> {
-    commands: HashMap<String, ConvertFn<T>>,
+enum Conflict {
+    Command(String),
+    Localization(String),
+}
+
+pub struct CommandHandler<T: ServiceHandler>
+where
+    T::ScopeType: Sync,
+{
+    commands: HashMap<String, CommandRoute<T>>,
+    context_menus: HashMap<(CommandType, String), ConvertFn<T>>,
+    components: Vec<(&'static str, bool, ConvertFn<T>)>,
+    modals: Vec<(&'static str, bool, ConvertFn<T>)>,
+    definitions: Vec<Command>,
+    localization_keys: Vec<LocalizationKey>,
+    localizations: Localizations,
+    responder: Option<Arc<dyn Responder>>,
+    error_reply: Option<ErrorReplyFn>,
+    auto_defer: bool,
+    auto_defer_after: Duration,
+    auto_defer_commands: HashMap<String, bool>,
+    ephemeral_commands: HashSet<(CommandType, String)>,
+    conflicts: Vec<Conflict>,
+}
+
+impl<T: ServiceHandler> fmt::Debug for CommandHandler<T>
+where
+    T::ScopeType: Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandHandler")
+            .field("commands", &self.commands)
+            .field("context_menus", &self.context_menus)
+            .field("components", &self.components)
+            .field("modals", &self.modals)
+            .field("definitions", &self.definitions)
+            .field("localizations", &self.localizations)
+            .finish_non_exhaustive()
+    }
 }
 
-impl<T: ServiceHandler> CommandHandler<T> {
+impl<T: ServiceHandler> CommandHandler<T>
+where
+    T::ScopeType: Sync,
+{
     pub fn new() -> Self {
         CommandHandler {
             commands: Default::default(),
+            context_menus: Default::default(),
+            components: Default::default(),
+            modals: Default::default(),
+            definitions: Default::default(),
+            localization_keys: Default::default(),
+            localizations: Default::default(),
+            responder: None,
+            error_reply: None,
+            auto_defer: false,
+            auto_defer_after: DEFAULT_AUTO_DEFER_AFTER,
+            auto_defer_commands: Default::default(),
+            ephemeral_commands: Default::default(),
+            conflicts: Default::default(),
+        }
+    }
+
+    /// Adds the commands, components and modals of a controller, sub commands
+    /// of a top level command can be spread over several controllers
+    ///
+    /// Commands that conflict with already added ones are reported by
+    /// [`CommandHandler::build_commands`].
+    pub fn add_command<C: CommandController<T::ScopeType> + Any + Send + Sync>(mut self) -> Self {
+        for command in C::build_commands() {
+            if command.kind == CommandType::ChatInput {
+                for route in register::command_routes(&command) {
+                    self.commands.insert(
+                        route,
+                        CommandRoute::Controller(Self::resolve_controller::<C>),
+                    );
+                }
+            } else {
+                self.context_menus.insert(
+                    (command.kind, command.name.clone()),
+                    Self::resolve_controller::<C>,
+                );
+            }
+
+            // A route that was overwritten above is always reported as a conflict here
+            let merged = register::merge_command(&mut self.definitions, command);
+            self.record_conflict(merged);
+        }
+
+        for (route, auto_defer) in C::get_auto_defer_commands() {
+            self.auto_defer_commands
+                .insert(route.to_string(), *auto_defer);
+        }
+
+        self.ephemeral_commands.extend(
+            C::get_ephemeral_commands()
+                .iter()
+                .map(|(kind, route)| (*kind, route.to_string())),
+        );
+
+        for pattern in C::get_component_patterns() {
+            self.components
+                .push((pattern, C::is_ephemeral(), Self::resolve_controller::<C>));
+        }
+
+        for pattern in C::get_modal_patterns() {
+            self.modals
+                .push((pattern, C::is_ephemeral(), Self::resolve_controller::<C>));
+        }
+
+        let merged = register::merge_localization_keys(
+            &mut self.localization_keys,
+            C::get_localization_keys(),
+        );
+        self.record_conflict(merged);
+
+        self
+    }
+
+    /// Adds a command that is handled by a function instead of a controller,
+    /// these commands can't have autocomplete handlers
+    pub fn command<F, Fut, R, E>(mut self, spec: CommandSpec, command_fn: F) -> Self
+    where
+        T::ScopeType: Send + 'static,
+        F: Fn(CommandContext<T::ScopeType>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        R: IntoResponse,
+        E: Into<Error>,
+    {
+        let auto_defer = spec.get_auto_defer();
+        let ephemeral = spec.is_ephemeral();
+        let command = spec.build();
+        let command_fn: CommandFn<T> = Arc::new(move |context| {
+            let future = command_fn(context);
+            Box::pin(async move {
+                match future.await {
+                    Ok(r) => Ok(r.into_response()),
+                    Err(e) => Err(e.into()),
+                }
+            })
+        });
+
+        for route in register::command_routes(&command) {
+            if let Some(auto_defer) = auto_defer {
+                self.auto_defer_commands.insert(route.clone(), auto_defer);
+            }
+            if ephemeral {
+                self.ephemeral_commands
+                    .insert((CommandType::ChatInput, route.clone()));
+            }
+            self.commands
+                .insert(route, CommandRoute::Function(command_fn.clone()));
+        }
+        let merged = register::merge_command(&mut self.definitions, command);
+        self.record_conflict(merged);
+
+        self
+    }
+
+    fn record_conflict(&mut self, merged: Result<(), Error>) {
+        match merged {
+            Err(Error::CommandConflict { name }) => self.conflicts.push(Conflict::Command(name)),
+            Err(Error::LocalizationConflict { path }) => {
+                self.conflicts.push(Conflict::Localization(path))
+            }
+            _ => {}
+        }
+    }
+
+    /// Sets what sends the responses returned by handlers, handlers that
+    /// return a response fail with [`Error::NoResponder`] without one
+    pub fn responder(mut self, responder: impl Responder + 'static) -> Self {
+        self.responder = Some(Arc::new(responder));
+        self
+    }
+
+    /// Replies to interactions whose handler failed, the reply is ephemeral
+    /// if the handler is and replaces the loading message of a deferred
+    /// interaction
+    ///
+    /// Nothing is sent if the handler already responded, the handler's error
+    /// is still returned and takes precedence over a reply that fails.
+    pub fn error_reply<F, R>(mut self, reply: F) -> Self
+    where
+        F: Fn(&Error) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.error_reply = Some(Arc::new(move |error| reply(error).into_response()));
+        self
+    }
+
+    /// Defers every command that hasn't responded yet once the threshold of
+    /// [`CommandHandler::auto_defer_after`] has passed, commands can opt out
+    /// with `#[command(auto_defer = false)]`
+    ///
+    /// The deferral is sent by the responder, deferred commands fail with
+    /// [`Error::NoResponder`] without one.
+    pub fn auto_defer(mut self, auto_defer: bool) -> Self {
+        self.auto_defer = auto_defer;
+        self
+    }
+
+    /// How long a command can take before it is deferred, Discord fails the
+    /// interaction after three seconds
+    pub fn auto_defer_after(mut self, after: Duration) -> Self {
+        self.auto_defer_after = after;
+        self
+    }
+
+    /// Runs a command, deferring the interaction if the command hasn't
+    /// responded in time so the response returned later edits the deferral
+    async fn run_command<F>(
+        &self,
+        interaction: &InteractionCreate,
+        acknowledgement: &Acknowledgement,
+        auto_defer: bool,
+        command: F,
+    ) -> Result<Option<InteractionResponse>, Error>
+    where
+        F: Future<Output = Result<Option<InteractionResponse>, Error>>,
+    {
+        if !auto_defer {
+            return command.await;
+        }
+        if self.responder.is_none() {
+            return Err(Error::NoResponder);
+        }
+
+        tokio::pin!(command);
+        tokio::select! {
+            result = &mut command => return result,
+            _ = tokio::time::sleep(self.auto_defer_after) => {}
         }
+
+        if acknowledgement.state() == AcknowledgementState::Pending {
+            let deferred = self
+                .send_response(interaction, acknowledgement, Defer::new().into_response())
+                .await;
+
+            // The command still runs to completion, an error of its own is
+            // reported over the failed deferral
+            if let Err(e) = deferred {
+                command.await?;
+                return Err(e);
+            }
+        }
+
+        command.await
     }
 
-    pub fn add_command<C: CommandController + Any + Send + Sync>(mut self) -> Self {
-        for name in C::get_command_names() {
-            self.commands.insert(name.to_string(), |h: &T::ScopeType| {
-                h.get_service_by_type_id(&TypeId::of::<C>())
-                    .unwrap()
-                    .downcast::<C>()
-                    .unwrap() as Arc<dyn CommandController>
-            });
+    /// Sends the response of a handler, or the error reply if it failed
+    async fn finish(
+        &self,
+        interaction: &InteractionCreate,
+        acknowledgement: &Acknowledgement,
+        result: Result<Option<InteractionResponse>, Error>,
+    ) -> Result<(), Error> {
+        let error = match result {
+            Ok(response) => {
+                return self
+                    .send_response(interaction, acknowledgement, response)
+                    .await
+            }
+            Err(e) => e,
+        };
+
+        if let Some(reply) = &self.error_reply {
+            if acknowledgement.state() != AcknowledgementState::Responded {
+                // The handler's error is what the caller needs to see
+                let _ = self
+                    .send_response(interaction, acknowledgement, reply(&error))
+                    .await;
+            }
         }
 
+        Err(error)
+    }
+
+    async fn send_response(
+        &self,
+        interaction: &InteractionCreate,
+        acknowledgement: &Acknowledgement,
+        response: Option<InteractionResponse>,
+    ) -> Result<(), Error> {
+        let Some(response) = response else {
+            return Ok(());
+        };
+        let Some(responder) = &self.responder else {
+            return Err(Error::NoResponder);
+        };
+
+        response::send(responder.as_ref(), interaction, acknowledgement, response).await
+    }
+
+    /// Sets the translations that are applied to the commands returned by
+    /// [`CommandHandler::build_commands`]
+    pub fn localizations(mut self, localizations: Localizations) -> Self {
+        self.localizations = localizations;
         self
     }
 
+    /// Builds the commands of every added controller with the translations
+    /// of their localization keys, fails with the first command that was
+    /// declared twice or given two localization keys, or has options that
+    /// Discord would reject
+    pub fn build_commands(&self) -> Result<Vec<Command>, Error> {
+        match self.conflicts.first() {
+            Some(Conflict::Command(name)) => {
+                return Err(Error::CommandConflict { name: name.clone() })
+            }
+            Some(Conflict::Localization(path)) => {
+                return Err(Error::LocalizationConflict { path: path.clone() })
+            }
+            None => {}
+        }
+
+        for command in &self.definitions {
+            register::check_options(command)?;
+        }
+
+        let mut commands = self.definitions.clone();
+        self.localizations
+            .localize(&mut commands, &self.localization_keys);
+
+        Ok(commands)
+    }
+
+    /// Returns every translation used by the added controllers that is
+    /// missing from one of the loaded locales
+    pub fn missing_localizations(&self) -> Vec<MissingLocalization> {
+        self.localizations.missing(&self.localization_keys)
+    }
+
+    fn resolve_controller<C: CommandController<T::ScopeType> + Any + Send + Sync>(
+        h: &T::ScopeType,
+    ) -> Arc<dyn CommandController<T::ScopeType> + 'static> {
+        h.get_service_by_type_id(&TypeId::of::<C>())
+            .unwrap()
+            .downcast::<C>()
+            .unwrap() as Arc<dyn CommandController<T::ScopeType>>
+    }
+
     pub async fn handle_command_interaction(
         &self,
         interaction: &InteractionCreate,
         handler: &T,
     ) -> Result<(), Error> {
+        if interaction.kind != InteractionType::ApplicationCommand {
+            return Err(Error::NotApplicationCommand);
+        }
+
         let data = match &interaction.data {
             Some(InteractionData::ApplicationCommand(ap)) => ap,
             _ => return Err(Error::NotApplicationCommand),
         };
 
-        let fn_ = match self.commands.get(&data.name) {
-            Some(t) => t,
-            None => return Err(Error::CommandNotFound),
+        let route = command_route(data);
+        let auto_defer = match data.kind {
+            CommandType::ChatInput => self.auto_defer_commands.get(&route).copied(),
+            _ => None,
+        }
+        .unwrap_or(self.auto_defer);
+        // Context menus have no options, so their route is their name
+        let ephemeral = self
+            .ephemeral_commands
+            .contains(&(data.kind, route.clone()));
+
+        let fn_ = match data.kind {
+            CommandType::ChatInput => match self.commands.get(&route) {
+                Some(CommandRoute::Controller(fn_)) => Some(fn_),
+                Some(CommandRoute::Function(command_fn)) => {
+                    let acknowledgement = Acknowledgement::new(ephemeral);
+                    let context = CommandContext {
+                        interaction: interaction.clone(),
+                        data: data.deref().clone(),
+                        services: handler.create_scope(),
+                        acknowledgement: acknowledgement.clone(),
+                    };
+
+                    let result = self
+                        .run_command(
+                            interaction,
+                            &acknowledgement,
+                            auto_defer,
+                            command_fn(context),
+                        )
+                        .await;
+                    return self.finish(interaction, &acknowledgement, result).await;
+                }
+                None => None,
+            },
+            kind => self.context_menus.get(&(kind, data.name.clone())),
+        };
+        let Some(fn_) = fn_ else {
+            return Err(Error::CommandNotFound);
+        };
+
+        let scope = handler.create_scope();
+        let command_controller = fn_(&scope);
+        let acknowledgement = Acknowledgement::new(ephemeral);
+
+        let command =
+            command_controller.execute_command(interaction, data, &scope, &acknowledgement);
+        let result = self
+            .run_command(interaction, &acknowledgement, auto_defer, command)
+            .await;
+        self.finish(interaction, &acknowledgement, result).await
+    }
+
+    /// Runs the component handler whose custom id pattern matches the
+    /// interaction, patterns are tried in the order they were added
+    pub async fn handle_component_interaction(
+        &self,
+        interaction: &InteractionCreate,
+        handler: &T,
+    ) -> Result<(), Error> {
+        let data = match &interaction.data {
+            Some(InteractionData::MessageComponent(d)) => d,
+            _ => return Err(Error::NotMessageComponent),
+        };
+
+        let Some((_, ephemeral, fn_)) = self
+            .components
+            .iter()
+            .find(|(p, _, _)| component::match_custom_id(p, &data.custom_id).is_some())
+        else {
+            return Err(Error::ComponentNotFound);
+        };
+
+        let scope = handler.create_scope();
+        let command_controller = fn_(&scope);
+        let acknowledgement = Acknowledgement::new(*ephemeral);
+
+        let result = command_controller
+            .execute_component(interaction, data, &scope, &acknowledgement)
+            .await;
+        self.finish(interaction, &acknowledgement, result).await
+    }
+
+    /// Runs the modal handler whose custom id pattern matches the
+    /// interaction, patterns are tried in the order they were added
+    pub async fn handle_modal_interaction(
+        &self,
+        interaction: &InteractionCreate,
+        handler: &T,
+    ) -> Result<(), Error> {
+        let data = match &interaction.data {
+            Some(InteractionData::ModalSubmit(d)) => d,
+            _ => return Err(Error::NotModalSubmit),
+        };
+
+        let Some((_, ephemeral, fn_)) = self
+            .modals
+            .iter()
+            .find(|(p, _, _)| component::match_custom_id(p, &data.custom_id).is_some())
+        else {
+            return Err(Error::ModalNotFound);
+        };
+
+        let scope = handler.create_scope();
+        let command_controller = fn_(&scope);
+        let acknowledgement = Acknowledgement::new(*ephemeral);
+
+        let result = command_controller
+            .execute_modal(interaction, data, &scope, &acknowledgement)
+            .await;
+        self.finish(interaction, &acknowledgement, result).await
+    }
+
+    /// Runs the autocomplete handler of the focused option and sends its
+    /// choices through the responder, choices past the first 25 are dropped
+    /// since Discord rejects the result otherwise
+    pub async fn handle_autocomplete_interaction(
+        &self,
+        interaction: &InteractionCreate,
+        handler: &T,
+    ) -> Result<(), Error> {
+        if interaction.kind != InteractionType::ApplicationCommandAutocomplete {
+            return Err(Error::NotAutocomplete);
+        }
+
+        let data = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(ap)) => ap,
+            _ => return Err(Error::NotAutocomplete),
+        };
+
+        let Some(CommandRoute::Controller(fn_)) = self.commands.get(&command_route(data)) else {
+            return Err(Error::CommandNotFound);
         };
 
         let scope = handler.create_scope();
         let command_controller = fn_(&scope);
 
-        command_controller.execute_command(interaction, data).await
+        let mut choices = command_controller
+            .execute_autocomplete(interaction, data, &scope)
+            .await?;
+        choices.truncate(MAX_AUTOCOMPLETE_CHOICES);
+
+        let response = InteractionResponse {
+            kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+            data: Some(
+                InteractionResponseDataBuilder::new()
+                    .choices(choices)
+                    .build(),
+            ),
+        };
+        self.send_response(interaction, &Acknowledgement::default(), Some(response))
+            .await
+    }
+}
+
+/// The route of an invoked chat input command, see [`register::command_routes`]
+fn command_route(data: &CommandData) -> String {
+    let Some(option) = data.options.first() else {
+        return data.name.clone();
+    };
+
+    match &option.value {
+        CommandOptionValue::SubCommand(_) => format!("{} {}", data.name, option.name),
+        CommandOptionValue::SubCommandGroup(subs) => match subs.first() {
+            Some(sub) => format!("{} {} {}", data.name, option.name, sub.name),
+            None => format!("{} {}", data.name, option.name),
+        },
+        _ => data.name.clone(),
     }
 }
 
-impl<T: ServiceHandler> Default for CommandHandler<T> {
+impl<T: ServiceHandler> Default for CommandHandler<T>
+where
+    T::ScopeType: Sync,
+{
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deppy::{ServiceCollection, ServiceCollectionBuilder};
+    use std::sync::Mutex;
+    use twilight_model::http::interaction::InteractionResponseData;
+
+    /// Records what was sent, `None` for an edit of the original response
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<Option<InteractionResponseType>>>,
+        fail: bool,
+    }
+
+    impl Recorder {
+        fn record(
+            &self,
+            call: Option<InteractionResponseType>,
+        ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+            self.calls.lock().unwrap().push(call);
+            if self.fail {
+                return Err("sending failed".into());
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Responder for Recorder {
+        async fn respond(
+            &self,
+            _: &InteractionCreate,
+            response: &InteractionResponse,
+        ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+            self.record(Some(response.kind))
+        }
+
+        async fn edit_original(
+            &self,
+            _: &InteractionCreate,
+            _: &InteractionResponseData,
+        ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+            self.record(None)
+        }
+    }
+
+    fn interaction(name: &str) -> InteractionCreate {
+        let interaction = serde_json::json!({
+            "id": "1",
+            "application_id": "2",
+            "type": 2,
+            "token": "token",
+            "version": 1,
+            "channel_id": "3",
+            "locale": "en-US",
+            "entitlements": [],
+            "authorizing_integration_owners": {},
+            "user": {"id": "4", "username": "user", "discriminator": "0", "avatar": null},
+            "data": {"id": "5", "name": name, "type": 1},
+        });
+        InteractionCreate(serde_json::from_value(interaction).unwrap())
+    }
+
+    /// A handler with a command that takes `delay` and a command `opt-out`
+    /// that takes as long but isn't deferred
+    fn handler(
+        responder: &Arc<Recorder>,
+        delay: Duration,
+        result: fn() -> Result<&'static str, Error>,
+    ) -> CommandHandler<ServiceCollection> {
+        let command = move |_| async move {
+            tokio::time::sleep(delay).await;
+            result()
+        };
+
+        CommandHandler::new()
+            .command(CommandSpec::new("run", "Runs"), command)
+            .command(
+                CommandSpec::new("opt-out", "Runs").auto_defer(false),
+                command,
+            )
+            .auto_defer(true)
+            .responder(responder.clone())
+    }
+
+    async fn run(handler: &CommandHandler<ServiceCollection>, name: &str) -> Result<(), Error> {
+        let services = ServiceCollectionBuilder::default().build();
+        handler
+            .handle_command_interaction(&interaction(name), &services)
+            .await
+    }
+
+    fn calls(responder: &Recorder) -> Vec<Option<InteractionResponseType>> {
+        responder.calls.lock().unwrap().clone()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fast_command_is_not_deferred() {
+        let responder = Arc::new(Recorder::default());
+        let handler = handler(&responder, Duration::from_millis(500), || Ok("done"));
+
+        run(&handler, "run").await.unwrap();
+        assert_eq!(
+            calls(&responder),
+            [Some(InteractionResponseType::ChannelMessageWithSource)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_command_is_deferred() {
+        let responder = Arc::new(Recorder::default());
+        let handler = handler(&responder, Duration::from_secs(5), || Ok("done"));
+
+        run(&handler, "run").await.unwrap();
+        assert_eq!(
+            calls(&responder),
+            [
+                Some(InteractionResponseType::DeferredChannelMessageWithSource),
+                None
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn route_opts_out_of_auto_defer() {
+        let responder = Arc::new(Recorder::default());
+        let handler = handler(&responder, Duration::from_secs(5), || Ok("done"));
+
+        run(&handler, "opt-out").await.unwrap();
+        assert_eq!(
+            calls(&responder),
+            [Some(InteractionResponseType::ChannelMessageWithSource)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_deferral_reports_command_error_first() {
+        let responder = Arc::new(Recorder {
+            fail: true,
+            ..Default::default()
+        });
+
+        let failing = handler(&responder, Duration::from_secs(5), || {
+            Err(Error::CommandNotFound)
+        });
+        let result = run(&failing, "run").await;
+        assert!(matches!(result, Err(Error::CommandNotFound)));
+
+        let succeeding = handler(&responder, Duration::from_secs(5), || Ok("done"));
+        let result = run(&succeeding, "run").await;
+        assert!(matches!(result, Err(Error::ResponseFailed { .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn auto_defer_requires_responder() {
+        let handler = CommandHandler::<ServiceCollection>::new()
+            .command(CommandSpec::new("run", "Runs"), |_| async move {
+                Ok::<_, Error>(())
+            })
+            .auto_defer(true);
+
+        assert!(matches!(
+            run(&handler, "run").await,
+            Err(Error::NoResponder)
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use twilight_model::application::command::{Command, CommandOption, CommandType};
+
+/// Where a localization key from a command attribute is applied.
+///
+/// `path` starts with the name of the top level command and continues with
+/// the names of the sub command groups, sub commands and options below it.
+/// Keys are looked up as `<key>.name` and, for everything that has a
+/// description, `<key>.description`.
+#[derive(Debug)]
+pub struct LocalizationKey {
+    pub kind: CommandType,
+    pub path: Vec<&'static str>,
+    pub choice: Option<&'static str>,
+    pub key: &'static str,
+}
+
+impl LocalizationKey {
+    fn has_description(&self) -> bool {
+        self.kind == CommandType::ChatInput && self.choice.is_none()
+    }
+}
+
+/// A translation that is used by a command but missing from a locale
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingLocalization {
+    pub locale: String,
+    pub key: String,
+}
+
+/// Translations for every locale, keyed by the locale first and the
+/// translation key second.
+///
+/// Locale files contain one `key = value` pair per line, lines that are
+/// empty or start with `#` are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct Localizations {
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl Localizations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_locale(mut self, locale: impl Into<String>, content: &str) -> Self {
+        let translations = self.locales.entry(locale.into()).or_default();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                translations.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        self
+    }
+
+    pub fn add_locale_file(
+        self,
+        locale: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(self.add_locale(locale, &content))
+    }
+
+    fn get(&self, key: &str) -> Option<HashMap<String, String>> {
+        let translations: HashMap<String, String> = self
+            .locales
+            .iter()
+            .filter_map(|(locale, t)| t.get(key).map(|v| (locale.clone(), v.clone())))
+            .collect();
+
+        if translations.is_empty() {
+            None
+        } else {
+            Some(translations)
+        }
+    }
+
+    pub fn localize(&self, commands: &mut [Command], keys: &[LocalizationKey]) {
+        for key in keys {
+            let Some((name, rest)) = key.path.split_first() else {
+                continue;
+            };
+
+            let Some(command) = commands
+                .iter_mut()
+                .find(|c| c.kind == key.kind && &c.name == name)
+            else {
+                continue;
+            };
+
+            if rest.is_empty() {
+                command.name_localizations = self.get(&format!("{}.name", key.key));
+                if key.has_description() {
+                    command.description_localizations =
+                        self.get(&format!("{}.description", key.key));
+                }
+                continue;
+            }
+
+            let Some(option) = find_option(&mut command.options, rest) else {
+                continue;
+            };
+
+            if let Some(choice) = key.choice {
+                let choice = option
+                    .choices
+                    .iter_mut()
+                    .flatten()
+                    .find(|c| c.name == choice);
+
+                if let Some(choice) = choice {
+                    choice.name_localizations = self.get(&format!("{}.name", key.key));
+                }
+            } else {
+                option.name_localizations = self.get(&format!("{}.name", key.key));
+                option.description_localizations = self.get(&format!("{}.description", key.key));
+            }
+        }
+    }
+
+    pub fn missing(&self, keys: &[LocalizationKey]) -> Vec<MissingLocalization> {
+        let mut missing = vec![];
+        for (locale, translations) in &self.locales {
+            for key in keys {
+                let mut needed = vec![format!("{}.name", key.key)];
+                if key.has_description() {
+                    needed.push(format!("{}.description", key.key));
+                }
+
+                for needed in needed {
+                    let entry = MissingLocalization {
+                        locale: locale.clone(),
+                        key: needed,
+                    };
+
+                    // The same key can be used by several commands
+                    if !translations.contains_key(&entry.key) && !missing.contains(&entry) {
+                        missing.push(entry);
+                    }
+                }
+            }
+        }
+
+        missing
+    }
+}
+
+fn find_option<'a>(
+    options: &'a mut [CommandOption],
+    path: &[&str],
+) -> Option<&'a mut CommandOption> {
+    let (name, rest) = path.split_first()?;
+    let option = options.iter_mut().find(|o| &o.name == name)?;
+
+    if rest.is_empty() {
+        Some(option)
+    } else {
+        find_option(option.options.as_deref_mut()?, rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use twilight_util::builder::command::{CommandBuilder, StringBuilder, SubCommandBuilder};
+
+    const GERMAN: &str = "
+        # Moderation
+        mod.name = moderation
+        mod.description = Moderiert den Server
+
+        mod.ban.name=bannen
+        broken line
+    ";
+
+    fn key(path: Vec<&'static str>, key: &'static str) -> LocalizationKey {
+        LocalizationKey {
+            kind: CommandType::ChatInput,
+            path,
+            choice: None,
+            key,
+        }
+    }
+
+    fn translation(locale: &str, value: &str) -> Option<HashMap<String, String>> {
+        Some(HashMap::from([(locale.to_string(), value.to_string())]))
+    }
+
+    #[test]
+    fn parses_locales() {
+        let localizations = Localizations::new().add_locale("de", GERMAN);
+
+        assert_eq!(
+            localizations.get("mod.name"),
+            translation("de", "moderation")
+        );
+        assert_eq!(
+            localizations.get("mod.description"),
+            translation("de", "Moderiert den Server")
+        );
+        assert_eq!(
+            localizations.get("mod.ban.name"),
+            translation("de", "bannen")
+        );
+        assert_eq!(localizations.get("broken line"), None);
+        assert_eq!(localizations.locales["de"].len(), 3);
+    }
+
+    #[test]
+    fn later_locales_extend_earlier_ones() {
+        let localizations = Localizations::new()
+            .add_locale("de", "a.name = a")
+            .add_locale("de", "b.name = b")
+            .add_locale("fr", "a.name = le a");
+
+        assert_eq!(localizations.locales["de"].len(), 2);
+        assert_eq!(localizations.get("a.name").map(|t| t.len()), Some(2));
+    }
+
+    #[test]
+    fn localizes_commands_and_options() {
+        let localizations = Localizations::new().add_locale("de", GERMAN);
+        let mut commands = vec![
+            CommandBuilder::new("mod", "Moderates", CommandType::ChatInput)
+                .option(
+                    SubCommandBuilder::new("ban", "Bans").option(StringBuilder::new("reason", "r")),
+                )
+                .build(),
+        ];
+
+        localizations.localize(
+            &mut commands,
+            &[key(vec!["mod"], "mod"), key(vec!["mod", "ban"], "mod.ban")],
+        );
+
+        assert_eq!(
+            commands[0].name_localizations,
+            translation("de", "moderation")
+        );
+        assert_eq!(
+            commands[0].description_localizations,
+            translation("de", "Moderiert den Server")
+        );
+        assert_eq!(
+            commands[0].options[0].name_localizations,
+            translation("de", "bannen")
+        );
+        assert_eq!(commands[0].options[0].description_localizations, None);
+    }
+
+    #[test]
+    fn reports_missing_translations() {
+        let localizations = Localizations::new().add_locale("de", GERMAN);
+        let keys = [
+            key(vec!["mod"], "mod"),
+            key(vec!["mod", "ban"], "mod.ban"),
+            key(vec!["mod", "kick"], "mod.ban"),
+            LocalizationKey {
+                kind: CommandType::User,
+                path: vec!["Report"],
+                choice: None,
+                key: "report",
+            },
+        ];
+
+        let missing = localizations.missing(&keys);
+        let missing = missing.iter().map(|m| m.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(missing, ["mod.ban.description", "report.name"]);
+    }
+
+    #[test]
+    fn nothing_is_missing_without_locales() {
+        assert!(Localizations::new()
+            .missing(&[key(vec!["mod"], "mod")])
+            .is_empty());
+    }
+}
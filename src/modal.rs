@@ -0,0 +1,26 @@
+use twilight_model::application::interaction::modal::ModalInteractionData;
+use twilight_model::http::interaction::InteractionResponse;
+
+/// A form that can be shown to the user as a modal and parsed back from
+/// the submitted data, usually implemented with `#[derive(Modal)]`
+pub trait Modal {
+    /// Creates the response that opens the modal with the given custom id
+    fn create_modal(custom_id: &str) -> InteractionResponse
+    where
+        Self: Sized;
+
+    fn parse_modal(data: &ModalInteractionData) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+pub fn get_text_input_value<'a>(
+    data: &'a ModalInteractionData,
+    custom_id: &str,
+) -> Option<&'a str> {
+    data.components
+        .iter()
+        .flat_map(|r| r.components.iter())
+        .find(|c| c.custom_id == custom_id)
+        .and_then(|c| c.value.as_deref())
+}
@@ -1,9 +1,19 @@
+use crate::localization::LocalizationKey;
+use crate::{Error, ResolvedMember};
 use twilight_model::{
-    application::command::CommandOption,
+    application::{
+        command::{Command, CommandOption, CommandOptionType, CommandType},
+        interaction::{
+            application_command::CommandOptionValue, InteractionChannel, InteractionDataResolved,
+        },
+    },
+    channel::{Attachment, ChannelType},
+    guild::Role,
     id::{
         marker::{AttachmentMarker, ChannelMarker, GenericMarker, RoleMarker, UserMarker},
         Id,
     },
+    user::User,
 };
 
 use twilight_util::builder::command::{
@@ -11,16 +21,106 @@ use twilight_util::builder::command::{
     NumberBuilder, RoleBuilder, StringBuilder, UserBuilder,
 };
 
+/// Limits of an option that are registered with Discord and checked again
+/// when the option is bound, as the registered command can be edited
+#[derive(Debug, Clone, Default)]
+pub struct OptionConstraints {
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub min_length: Option<u16>,
+    pub max_length: Option<u16>,
+    pub channel_types: Vec<ChannelType>,
+}
+
+impl OptionConstraints {
+    pub fn is_satisfied_by(
+        &self,
+        value: &CommandOptionValue,
+        resolved: Option<&InteractionDataResolved>,
+    ) -> bool {
+        // Limits of integer options are registered truncated, see `integer_range`
+        let range = match value {
+            CommandOptionValue::Integer(v) => Some((
+                *v as f64,
+                self.min_value.map(f64::trunc),
+                self.max_value.map(f64::trunc),
+            )),
+            CommandOptionValue::Number(v) => Some((*v, self.min_value, self.max_value)),
+            _ => None,
+        };
+        if let Some((number, min, max)) = range {
+            if min.is_some_and(|min| number < min) || max.is_some_and(|max| number > max) {
+                return false;
+            }
+        }
+
+        if let CommandOptionValue::String(v) = value {
+            let length = v.chars().count();
+            if self.min_length.is_some_and(|min| length < min as usize)
+                || self.max_length.is_some_and(|max| length > max as usize)
+            {
+                return false;
+            }
+        }
+
+        if let CommandOptionValue::Channel(id) = value {
+            if !self.channel_types.is_empty() {
+                let kind = resolved.and_then(|r| r.channels.get(id)).map(|c| c.kind);
+
+                return kind.is_some_and(|k| self.channel_types.contains(&k));
+            }
+        }
+
+        true
+    }
+
+    fn integer_range(&self, min: i64, max: Option<i64>) -> (i64, Option<i64>) {
+        let min = self.min_value.map_or(min, |v| (v as i64).max(min));
+        let max = match (self.max_value, max) {
+            (Some(v), Some(max)) => Some((v as i64).min(max)),
+            (Some(v), None) => Some(v as i64),
+            (None, max) => max,
+        };
+
+        (min, max)
+    }
+}
+
 pub trait CreateOption {
     fn create_option(
         name: &str,
         description: &str,
         required: bool,
         choices: Vec<(String, Self)>,
-
+        constraints: &OptionConstraints,
     ) -> CommandOption
     where
         Self: Sized;
+
+    /// The type of the option that is registered, bound options of another
+    /// type are reported with it
+    fn option_type() -> CommandOptionType
+    where
+        Self: Sized,
+    {
+        Self::create_option(
+            "option",
+            "option",
+            false,
+            vec![],
+            &OptionConstraints::default(),
+        )
+        .kind
+    }
+
+    /// Localization keys of the choices the type registers by itself, as
+    /// pairs of the choice name and the key
+    fn choice_localizations() -> &'static [(&'static str, &'static str)]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
 }
 
 impl CreateOption for Id<AttachmentMarker> {
@@ -29,7 +129,7 @@ impl CreateOption for Id<AttachmentMarker> {
         description: &str,
         required: bool,
         _: Vec<(String, Self)>,
-
+        _: &OptionConstraints,
     ) -> CommandOption {
         AttachmentBuilder::new(name, description)
             .required(required)
@@ -43,7 +143,7 @@ impl CreateOption for bool {
         description: &str,
         required: bool,
         _: Vec<(String, Self)>,
-
+        _: &OptionConstraints,
     ) -> CommandOption {
         BooleanBuilder::new(name, description)
             .required(required)
@@ -57,11 +157,14 @@ impl CreateOption for Id<ChannelMarker> {
         description: &str,
         required: bool,
         _: Vec<(String, Self)>,
-
+        constraints: &OptionConstraints,
     ) -> CommandOption {
-        ChannelBuilder::new(name, description)
-            .required(required)
-            .build()
+        let mut builder = ChannelBuilder::new(name, description).required(required);
+        if !constraints.channel_types.is_empty() {
+            builder = builder.channel_types(constraints.channel_types.clone());
+        }
+
+        builder.build()
     }
 }
 
@@ -71,22 +174,87 @@ impl CreateOption for i64 {
         description: &str,
         required: bool,
         choices: Vec<(String, Self)>,
-
+        constraints: &OptionConstraints,
     ) -> CommandOption {
-        IntegerBuilder::new(name, description)
+        let mut builder = IntegerBuilder::new(name, description)
             .required(required)
-            .choices(choices)
-            .build()
+            .choices(choices);
+        if let Some(min) = constraints.min_value {
+            builder = builder.min_value(min as i64);
+        }
+        if let Some(max) = constraints.max_value {
+            builder = builder.max_value(max as i64);
+        }
+
+        builder.build()
     }
 }
 
+macro_rules! impl_create_option_for_integer {
+    ($($ty:ty),* $(,)?) => {$(
+        impl CreateOption for $ty {
+            fn create_option(
+                name: &str,
+                description: &str,
+                required: bool,
+                choices: Vec<(String, Self)>,
+                constraints: &OptionConstraints,
+            ) -> CommandOption {
+                let (min, max) = constraints
+                    .integer_range(i64::from(<$ty>::MIN), Some(i64::from(<$ty>::MAX)));
+
+                IntegerBuilder::new(name, description)
+                    .required(required)
+                    .min_value(min)
+                    .max_value(max.unwrap())
+                    .choices(choices.into_iter().map(|(n, v)| (n, i64::from(v))))
+                    .build()
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_create_option_for_wide_unsigned {
+    ($($ty:ty),* $(,)?) => {$(
+        impl CreateOption for $ty {
+            fn create_option(
+                name: &str,
+                description: &str,
+                required: bool,
+                choices: Vec<(String, Self)>,
+                constraints: &OptionConstraints,
+            ) -> CommandOption {
+                // The upper bound doesn't fit in an i64, so only the lower one is registered
+                let (min, max) = constraints.integer_range(0, None);
+
+                let mut builder = IntegerBuilder::new(name, description)
+                    .required(required)
+                    .min_value(min)
+                    .choices(
+                        choices
+                            .into_iter()
+                            .filter_map(|(n, v)| i64::try_from(v).ok().map(|v| (n, v))),
+                    );
+                if let Some(max) = max {
+                    builder = builder.max_value(max);
+                }
+
+                builder.build()
+            }
+        }
+    )*};
+}
+
+impl_create_option_for_integer!(i8, i16, i32, u8, u16, u32);
+impl_create_option_for_wide_unsigned!(u64, usize);
+
 impl CreateOption for Id<GenericMarker> {
     fn create_option(
         name: &str,
         description: &str,
         required: bool,
         _: Vec<(String, Self)>,
-
+        _: &OptionConstraints,
     ) -> CommandOption {
         MentionableBuilder::new(name, description)
             .required(required)
@@ -100,12 +268,19 @@ impl CreateOption for f64 {
         description: &str,
         required: bool,
         choices: Vec<(String, Self)>,
-
+        constraints: &OptionConstraints,
     ) -> CommandOption {
-        NumberBuilder::new(name, description)
+        let mut builder = NumberBuilder::new(name, description)
             .required(required)
-            .choices(choices)
-            .build()
+            .choices(choices);
+        if let Some(min) = constraints.min_value {
+            builder = builder.min_value(min);
+        }
+        if let Some(max) = constraints.max_value {
+            builder = builder.max_value(max);
+        }
+
+        builder.build()
     }
 }
 
@@ -115,7 +290,7 @@ impl CreateOption for Id<RoleMarker> {
         description: &str,
         required: bool,
         _: Vec<(String, Self)>,
-
+        _: &OptionConstraints,
     ) -> CommandOption {
         RoleBuilder::new(name, description)
             .required(required)
@@ -129,12 +304,19 @@ impl CreateOption for String {
         description: &str,
         required: bool,
         choices: Vec<(String, Self)>,
-
+        constraints: &OptionConstraints,
     ) -> CommandOption {
-        StringBuilder::new(name, description)
+        let mut builder = StringBuilder::new(name, description)
             .required(required)
-            .choices(choices)
-            .build()
+            .choices(choices);
+        if let Some(min) = constraints.min_length {
+            builder = builder.min_length(min);
+        }
+        if let Some(max) = constraints.max_length {
+            builder = builder.max_length(max);
+        }
+
+        builder.build()
     }
 }
 
@@ -144,10 +326,569 @@ impl CreateOption for Id<UserMarker> {
         description: &str,
         required: bool,
         _: Vec<(String, Self)>,
-
+        _: &OptionConstraints,
     ) -> CommandOption {
         UserBuilder::new(name, description)
             .required(required)
             .build()
     }
 }
+
+macro_rules! impl_create_option_for_resolved {
+    ($($ty:ty => $id:ty),* $(,)?) => {$(
+        impl CreateOption for $ty {
+            fn create_option(
+                name: &str,
+                description: &str,
+                required: bool,
+                _: Vec<(String, Self)>,
+                constraints: &OptionConstraints,
+            ) -> CommandOption {
+                <$id>::create_option(name, description, required, vec![], constraints)
+            }
+        }
+    )*};
+}
+
+impl_create_option_for_resolved!(
+    Attachment => Id<AttachmentMarker>,
+    InteractionChannel => Id<ChannelMarker>,
+    Role => Id<RoleMarker>,
+    User => Id<UserMarker>,
+    ResolvedMember => Id<UserMarker>,
+);
+
+fn has_subcommands(options: &[CommandOption]) -> bool {
+    options.iter().any(|o| {
+        matches!(
+            o.kind,
+            CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+        )
+    })
+}
+
+/// The routes a command is dispatched by, which are the names of the top
+/// level command, the sub command group and the sub command joined by spaces
+pub(crate) fn command_routes(command: &Command) -> Vec<String> {
+    if !has_subcommands(&command.options) {
+        return vec![command.name.clone()];
+    }
+
+    let mut routes = vec![];
+    for option in &command.options {
+        let route = format!("{} {}", command.name, option.name);
+        if option.kind == CommandOptionType::SubCommandGroup {
+            for sub in option.options.iter().flatten() {
+                routes.push(format!("{} {}", route, sub.name));
+            }
+        } else {
+            routes.push(route);
+        }
+    }
+
+    routes
+}
+
+/// Whether two declarations of a top level command agree on everything but
+/// their sub commands, so it doesn't matter which one is kept
+fn same_top_level(a: &Command, b: &Command) -> bool {
+    a.description == b.description
+        && a.default_member_permissions == b.default_member_permissions
+        && a.dm_permission == b.dm_permission
+        && a.nsfw == b.nsfw
+        && a.name_localizations == b.name_localizations
+        && a.description_localizations == b.description_localizations
+}
+
+/// Adds a command to the already registered ones, sub commands and groups of
+/// a top level command that already exists are merged into it if both
+/// declare the top level command and its groups the same way
+pub(crate) fn merge_command(commands: &mut Vec<Command>, command: Command) -> Result<(), Error> {
+    let Some(target) = commands
+        .iter_mut()
+        .find(|c| c.kind == command.kind && c.name == command.name)
+    else {
+        commands.push(command);
+        return Ok(());
+    };
+
+    if command.kind != CommandType::ChatInput
+        || !has_subcommands(&target.options)
+        || !has_subcommands(&command.options)
+        || !same_top_level(target, &command)
+    {
+        return Err(Error::CommandConflict { name: command.name });
+    }
+
+    for option in command.options {
+        let name = format!("{} {}", command.name, option.name);
+        let Some(existing) = target.options.iter_mut().find(|o| o.name == option.name) else {
+            target.options.push(option);
+            continue;
+        };
+
+        if existing.kind != CommandOptionType::SubCommandGroup
+            || option.kind != CommandOptionType::SubCommandGroup
+            || existing.description != option.description
+            || existing.name_localizations != option.name_localizations
+            || existing.description_localizations != option.description_localizations
+        {
+            return Err(Error::CommandConflict { name });
+        }
+
+        let subs = existing.options.get_or_insert_with(Vec::new);
+        for sub in option.options.into_iter().flatten() {
+            if subs.iter().any(|s| s.name == sub.name) {
+                return Err(Error::CommandConflict {
+                    name: format!("{} {}", name, sub.name),
+                });
+            }
+            subs.push(sub);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the option lists of a command the way Discord does, options of
+/// `#[options]` structs are only combined with the others once the command
+/// is built so the macro can't check them
+pub(crate) fn check_options(command: &Command) -> Result<(), Error> {
+    check_option_list(&command.name, &command.options)
+}
+
+fn check_option_list(path: &str, options: &[CommandOption]) -> Result<(), Error> {
+    let invalid = |reason: String| Error::InvalidOptions {
+        command: path.to_string(),
+        reason,
+    };
+
+    if options.len() > 25 {
+        return Err(invalid("there are more than 25 options".to_string()));
+    }
+
+    for (i, option) in options.iter().enumerate() {
+        if options[..i].iter().any(|o| o.name == option.name) {
+            return Err(invalid(format!(
+                "there is more than one option named `{}`",
+                option.name
+            )));
+        }
+
+        let required = option.required.unwrap_or(false);
+        if required && options[..i].iter().any(|o| !o.required.unwrap_or(false)) {
+            return Err(invalid(format!(
+                "the required option `{}` comes after an optional option",
+                option.name
+            )));
+        }
+
+        if matches!(
+            option.kind,
+            CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+        ) {
+            let path = format!("{} {}", path, option.name);
+            check_option_list(&path, option.options.as_deref().unwrap_or_default())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the localization keys of a controller, a command or group that is
+/// merged from several controllers can only use one key
+pub(crate) fn merge_localization_keys(
+    keys: &mut Vec<LocalizationKey>,
+    new_keys: Vec<LocalizationKey>,
+) -> Result<(), Error> {
+    for key in new_keys {
+        let existing = keys
+            .iter()
+            .find(|k| k.kind == key.kind && k.path == key.path && k.choice == key.choice);
+
+        match existing {
+            Some(existing) if existing.key == key.key => {}
+            Some(_) => {
+                return Err(Error::LocalizationConflict {
+                    path: key.path.join(" "),
+                })
+            }
+            None => keys.push(key),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FromOption;
+    use twilight_model::application::command::CommandOptionValue as Limit;
+    use twilight_model::application::interaction::application_command::CommandData;
+    use twilight_util::builder::command::{
+        CommandBuilder, SubCommandBuilder, SubCommandGroupBuilder,
+    };
+
+    fn sub(name: &str) -> SubCommandBuilder {
+        SubCommandBuilder::new(name, "sub")
+    }
+
+    fn command(subs: &[&str]) -> CommandBuilder {
+        subs.iter().fold(
+            CommandBuilder::new("mod", "Moderation", CommandType::ChatInput),
+            |builder, name| builder.option(sub(name)),
+        )
+    }
+
+    fn group(name: &str, subs: &[&str]) -> SubCommandGroupBuilder {
+        SubCommandGroupBuilder::new(name, "group").subcommands(subs.iter().map(|s| sub(s)))
+    }
+
+    fn is_conflict(result: Result<(), Error>, expected: &str) -> bool {
+        matches!(result, Err(Error::CommandConflict { name }) if name == expected)
+    }
+
+    #[test]
+    fn routes_of_plain_command() {
+        let command = CommandBuilder::new("ping", "Ping", CommandType::ChatInput)
+            .option(StringBuilder::new("text", "text"))
+            .build();
+
+        assert_eq!(command_routes(&command), ["ping"]);
+    }
+
+    #[test]
+    fn routes_of_sub_commands_and_groups() {
+        let command = command(&["ban"])
+            .option(group("warn", &["add", "clear"]))
+            .build();
+
+        assert_eq!(
+            command_routes(&command),
+            ["mod ban", "mod warn add", "mod warn clear"]
+        );
+    }
+
+    #[test]
+    fn merges_sub_commands() {
+        let mut commands = vec![command(&["ban"]).build()];
+        merge_command(&mut commands, command(&["kick"]).build()).unwrap();
+
+        assert_eq!(commands.len(), 1);
+        assert_eq!(command_routes(&commands[0]), ["mod ban", "mod kick"]);
+    }
+
+    #[test]
+    fn merges_groups() {
+        let mut commands = vec![command(&[]).option(group("warn", &["add"])).build()];
+        merge_command(
+            &mut commands,
+            command(&[]).option(group("warn", &["clear"])).build(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            command_routes(&commands[0]),
+            ["mod warn add", "mod warn clear"]
+        );
+    }
+
+    #[test]
+    fn keeps_other_commands_apart() {
+        let mut commands = vec![command(&["ban"]).build()];
+        let ping = CommandBuilder::new("ping", "Ping", CommandType::ChatInput).build();
+        let user = CommandBuilder::new("mod", "", CommandType::User).build();
+        merge_command(&mut commands, ping).unwrap();
+        merge_command(&mut commands, user).unwrap();
+
+        assert_eq!(commands.len(), 3);
+    }
+
+    #[test]
+    fn rejects_duplicate_commands() {
+        let ping = CommandBuilder::new("ping", "Ping", CommandType::ChatInput).build();
+        let mut commands = vec![ping.clone()];
+
+        assert!(is_conflict(merge_command(&mut commands, ping), "ping"));
+    }
+
+    #[test]
+    fn rejects_duplicate_sub_commands() {
+        let mut commands = vec![command(&["ban"]).build()];
+
+        assert!(is_conflict(
+            merge_command(&mut commands, command(&["ban"]).build()),
+            "mod ban"
+        ));
+
+        let mut commands = vec![command(&[]).option(group("warn", &["add"])).build()];
+        assert!(is_conflict(
+            merge_command(
+                &mut commands,
+                command(&[]).option(group("warn", &["add"])).build()
+            ),
+            "mod warn add"
+        ));
+    }
+
+    #[test]
+    fn rejects_differing_top_level_settings() {
+        let mut commands = vec![command(&["ban"]).build()];
+
+        let description = CommandBuilder::new("mod", "Other", CommandType::ChatInput)
+            .option(sub("kick"))
+            .build();
+        assert!(is_conflict(
+            merge_command(&mut commands, description),
+            "mod"
+        ));
+
+        let dm = command(&["kick"]).dm_permission(false).build();
+        assert!(is_conflict(merge_command(&mut commands, dm), "mod"));
+
+        let nsfw = command(&["kick"]).nsfw(true).build();
+        assert!(is_conflict(merge_command(&mut commands, nsfw), "mod"));
+
+        assert_eq!(command_routes(&commands[0]), ["mod ban"]);
+    }
+
+    #[test]
+    fn rejects_differing_groups() {
+        let mut commands = vec![command(&[]).option(group("warn", &["add"])).build()];
+        let other = command(&[])
+            .option(SubCommandGroupBuilder::new("warn", "Other").subcommands([sub("clear")]))
+            .build();
+
+        assert!(is_conflict(merge_command(&mut commands, other), "mod warn"));
+    }
+
+    #[test]
+    fn rejects_localization_keys_for_the_same_path() {
+        let key = |key| LocalizationKey {
+            kind: CommandType::ChatInput,
+            path: vec!["mod"],
+            choice: None,
+            key,
+        };
+        let mut keys = vec![key("mod")];
+
+        merge_localization_keys(&mut keys, vec![key("mod")]).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert!(matches!(
+            merge_localization_keys(&mut keys, vec![key("moderation")]),
+            Err(Error::LocalizationConflict { path }) if path == "mod"
+        ));
+    }
+
+    fn is_invalid(result: Result<(), Error>, expected: &str) -> bool {
+        matches!(result, Err(Error::InvalidOptions { command, .. }) if command == expected)
+    }
+
+    #[test]
+    fn accepts_valid_options() {
+        let command = command(&[])
+            .option(sub("ban").option(UserBuilder::new("user", "user").required(true)))
+            .option(group("warn", &["add", "clear"]))
+            .build();
+
+        check_options(&command).unwrap();
+    }
+
+    #[test]
+    fn rejects_duplicate_options() {
+        let duplicate_option = command(&[])
+            .option(
+                sub("ban")
+                    .option(StringBuilder::new("reason", "reason"))
+                    .option(StringBuilder::new("reason", "reason")),
+            )
+            .build();
+
+        assert!(is_invalid(check_options(&duplicate_option), "mod ban"));
+        assert!(is_invalid(
+            check_options(&command(&["ban", "ban"]).build()),
+            "mod"
+        ));
+    }
+
+    #[test]
+    fn rejects_required_options_after_optional_ones() {
+        let command = CommandBuilder::new("ping", "Ping", CommandType::ChatInput)
+            .option(StringBuilder::new("text", "text"))
+            .option(IntegerBuilder::new("count", "count").required(true))
+            .build();
+
+        assert!(is_invalid(check_options(&command), "ping"));
+    }
+
+    #[test]
+    fn rejects_more_than_25_options() {
+        let names = (0..26).map(|i| i.to_string()).collect::<Vec<_>>();
+        let command = names.iter().fold(
+            CommandBuilder::new("ping", "Ping", CommandType::ChatInput),
+            |builder, name| builder.option(StringBuilder::new(name, "text")),
+        );
+
+        assert!(is_invalid(check_options(&command.build()), "ping"));
+    }
+
+    fn command_data() -> CommandData {
+        let data = serde_json::json!({"id": "1", "name": "purge", "type": 1});
+        serde_json::from_value(data).unwrap()
+    }
+
+    fn limits(option: &CommandOption) -> (Option<Limit>, Option<Limit>) {
+        (option.min_value, option.max_value)
+    }
+
+    #[test]
+    fn integer_types_register_their_range() {
+        let constraints = OptionConstraints::default();
+
+        assert_eq!(
+            limits(&u8::create_option("n", "n", true, vec![], &constraints)),
+            (Some(Limit::Integer(0)), Some(Limit::Integer(255)))
+        );
+        assert_eq!(
+            limits(&i8::create_option("n", "n", true, vec![], &constraints)),
+            (Some(Limit::Integer(-128)), Some(Limit::Integer(127)))
+        );
+        assert_eq!(
+            limits(&u64::create_option("n", "n", true, vec![], &constraints)),
+            (Some(Limit::Integer(0)), None)
+        );
+        assert_eq!(
+            limits(&i64::create_option("n", "n", true, vec![], &constraints)),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn integer_types_reject_values_out_of_range() {
+        let data = command_data();
+
+        assert_eq!(
+            u8::from_option(CommandOptionValue::Integer(255), &data),
+            Some(255)
+        );
+        assert_eq!(
+            u8::from_option(CommandOptionValue::Integer(300), &data),
+            None
+        );
+        assert_eq!(
+            u8::from_option(CommandOptionValue::Integer(-1), &data),
+            None
+        );
+        assert_eq!(
+            i8::from_option(CommandOptionValue::Integer(-128), &data),
+            Some(-128)
+        );
+        assert_eq!(
+            u64::from_option(CommandOptionValue::Integer(-1), &data),
+            None
+        );
+        assert_eq!(
+            i64::from_option(CommandOptionValue::Integer(i64::MIN), &data),
+            Some(i64::MIN)
+        );
+        assert_eq!(
+            u8::from_option(CommandOptionValue::String("1".into()), &data),
+            None
+        );
+    }
+
+    fn constraints(min_value: Option<f64>, max_value: Option<f64>) -> OptionConstraints {
+        OptionConstraints {
+            min_value,
+            max_value,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn constraints_check_value_range() {
+        let constraints = constraints(Some(1.0), Some(100.0));
+        let satisfied = |value| constraints.is_satisfied_by(&value, None);
+
+        assert!(satisfied(CommandOptionValue::Integer(1)));
+        assert!(satisfied(CommandOptionValue::Integer(100)));
+        assert!(!satisfied(CommandOptionValue::Integer(0)));
+        assert!(!satisfied(CommandOptionValue::Integer(10_000)));
+        assert!(satisfied(CommandOptionValue::Number(99.5)));
+        assert!(!satisfied(CommandOptionValue::Number(100.5)));
+    }
+
+    #[test]
+    fn constraints_check_string_length() {
+        let constraints = OptionConstraints {
+            min_length: Some(2),
+            max_length: Some(4),
+            ..Default::default()
+        };
+        let satisfied = |value: &str| {
+            constraints.is_satisfied_by(&CommandOptionValue::String(value.to_string()), None)
+        };
+
+        assert!(satisfied("ab"));
+        assert!(satisfied("äöüß"));
+        assert!(!satisfied("a"));
+        assert!(!satisfied("abcde"));
+    }
+
+    #[test]
+    fn constraints_check_channel_types() {
+        let constraints = OptionConstraints {
+            channel_types: vec![ChannelType::GuildText],
+            ..Default::default()
+        };
+        let resolved: InteractionDataResolved = serde_json::from_value(serde_json::json!({
+            "channels": {
+                "1": {"id": "1", "name": "text", "type": 0, "permissions": "0"},
+                "2": {"id": "2", "name": "voice", "type": 2, "permissions": "0"},
+            },
+        }))
+        .unwrap();
+        let satisfied = |id: u64, resolved| {
+            constraints.is_satisfied_by(&CommandOptionValue::Channel(Id::new(id)), resolved)
+        };
+
+        assert!(satisfied(1, Some(&resolved)));
+        assert!(!satisfied(2, Some(&resolved)));
+        assert!(!satisfied(3, Some(&resolved)));
+        assert!(!satisfied(1, None));
+        assert!(OptionConstraints::default()
+            .is_satisfied_by(&CommandOptionValue::Channel(Id::new(2)), Some(&resolved)));
+    }
+
+    #[test]
+    fn integer_range_clamps_limits_to_the_type() {
+        assert_eq!(
+            constraints(Some(-5.0), Some(1000.0)).integer_range(0, Some(255)),
+            (0, Some(255))
+        );
+        assert_eq!(
+            constraints(Some(10.0), Some(20.0)).integer_range(0, Some(255)),
+            (10, Some(20))
+        );
+        assert_eq!(constraints(None, None).integer_range(0, None), (0, None));
+        assert_eq!(
+            constraints(None, Some(50.0)).integer_range(0, None),
+            (0, Some(50))
+        );
+    }
+
+    #[test]
+    fn fractional_limits_of_integers_match_the_registered_ones() {
+        let constraints = constraints(Some(1.5), Some(9.5));
+        let (min, max) = constraints.integer_range(0, Some(255));
+        let satisfied =
+            |value| constraints.is_satisfied_by(&CommandOptionValue::Integer(value), None);
+
+        assert_eq!((min, max), (1, Some(9)));
+        assert!(satisfied(1));
+        assert!(satisfied(9));
+        assert!(!satisfied(0));
+        assert!(!satisfied(10));
+    }
+}
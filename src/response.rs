@@ -0,0 +1,548 @@
+use crate::Error;
+use async_trait::async_trait;
+use deppy::Dep;
+use std::error::Error as ErrorTrait;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use twilight_model::channel::message::{Component, Embed, MessageFlags};
+use twilight_model::gateway::payload::incoming::InteractionCreate;
+use twilight_model::http::interaction::{
+    InteractionResponse, InteractionResponseData, InteractionResponseType,
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+/// A value returned by a handler that is sent as the response to the
+/// interaction, `None` sends nothing for handlers that respond by themselves
+pub trait IntoResponse {
+    fn into_response(self) -> Option<InteractionResponse>;
+}
+
+impl IntoResponse for () {
+    fn into_response(self) -> Option<InteractionResponse> {
+        None
+    }
+}
+
+impl IntoResponse for InteractionResponse {
+    fn into_response(self) -> Option<InteractionResponse> {
+        Some(self)
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Option<InteractionResponse> {
+        Reply::new().content(self).into_response()
+    }
+}
+
+impl IntoResponse for &str {
+    fn into_response(self) -> Option<InteractionResponse> {
+        Reply::new().content(self).into_response()
+    }
+}
+
+impl IntoResponse for Embed {
+    fn into_response(self) -> Option<InteractionResponse> {
+        Reply::new().embed(self).into_response()
+    }
+}
+
+impl IntoResponse for Vec<Embed> {
+    fn into_response(self) -> Option<InteractionResponse> {
+        Reply::new().embeds(self).into_response()
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Option<T> {
+    fn into_response(self) -> Option<InteractionResponse> {
+        self.and_then(IntoResponse::into_response)
+    }
+}
+
+/// A message that is sent as the response to the interaction
+#[derive(Debug, Clone, Default)]
+pub struct Reply {
+    content: Option<String>,
+    embeds: Vec<Embed>,
+    components: Vec<Component>,
+    ephemeral: bool,
+}
+
+impl Reply {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    pub fn embeds(mut self, embeds: impl IntoIterator<Item = Embed>) -> Self {
+        self.embeds.extend(embeds);
+        self
+    }
+
+    pub fn components(mut self, components: impl IntoIterator<Item = Component>) -> Self {
+        self.components.extend(components);
+        self
+    }
+
+    /// Only shows the message to the user that used the interaction
+    pub fn ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        self
+    }
+}
+
+impl IntoResponse for Reply {
+    fn into_response(self) -> Option<InteractionResponse> {
+        let mut builder = InteractionResponseDataBuilder::new();
+        if let Some(content) = self.content {
+            builder = builder.content(content);
+        }
+        if !self.embeds.is_empty() {
+            builder = builder.embeds(self.embeds);
+        }
+        if !self.components.is_empty() {
+            builder = builder.components(self.components);
+        }
+        if self.ephemeral {
+            builder = builder.flags(MessageFlags::EPHEMERAL);
+        }
+
+        Some(InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(builder.build()),
+        })
+    }
+}
+
+/// Acknowledges the interaction so the actual message can be sent later as
+/// a follow-up or by editing the original response
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Defer {
+    ephemeral: bool,
+}
+
+impl Defer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only shows the message to the user that used the interaction
+    pub fn ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        self
+    }
+}
+
+impl IntoResponse for Defer {
+    fn into_response(self) -> Option<InteractionResponse> {
+        let data = if self.ephemeral {
+            Some(
+                InteractionResponseDataBuilder::new()
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build(),
+            )
+        } else {
+            None
+        };
+
+        Some(InteractionResponse {
+            kind: InteractionResponseType::DeferredChannelMessageWithSource,
+            data,
+        })
+    }
+}
+
+/// How far an interaction has been responded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AcknowledgementState {
+    #[default]
+    Pending,
+    Deferred,
+    Responded,
+}
+
+/// The state an interaction is in after the response was sent
+pub(crate) fn acknowledges_as(response: &InteractionResponse) -> AcknowledgementState {
+    match response.kind {
+        InteractionResponseType::DeferredChannelMessageWithSource
+        | InteractionResponseType::DeferredUpdateMessage => AcknowledgementState::Deferred,
+        _ => AcknowledgementState::Responded,
+    }
+}
+
+/// Whether the response is a message that can replace the loading message of
+/// a deferred interaction, anything else can only be the initial response
+fn is_message(response: &InteractionResponse) -> bool {
+    matches!(
+        response.kind,
+        InteractionResponseType::ChannelMessageWithSource | InteractionResponseType::UpdateMessage
+    )
+}
+
+/// Adds the ephemeral flag to responses that send a message, the flag of a
+/// deferral is kept by the message that replaces it
+fn set_ephemeral(response: &mut InteractionResponse) {
+    if !matches!(
+        response.kind,
+        InteractionResponseType::ChannelMessageWithSource
+            | InteractionResponseType::DeferredChannelMessageWithSource
+    ) {
+        return;
+    }
+
+    let data = response.data.get_or_insert_with(Default::default);
+    data.flags = Some(data.flags.unwrap_or(MessageFlags::empty()) | MessageFlags::EPHEMERAL);
+}
+
+/// The response state of one interaction, shared between the framework and
+/// everything that responds to the interaction while it is handled
+#[derive(Debug, Clone, Default)]
+pub struct Acknowledgement {
+    state: Arc<Mutex<AcknowledgementState>>,
+    ephemeral: bool,
+}
+
+impl Acknowledgement {
+    /// Every message sent through the acknowledgement is ephemeral if
+    /// `ephemeral` is set
+    pub fn new(ephemeral: bool) -> Self {
+        Self {
+            state: Default::default(),
+            ephemeral,
+        }
+    }
+
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    pub fn state(&self) -> AcknowledgementState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Moves to `state` if the current state is one of `from` and returns
+    /// the previous state, so it can be restored if sending fails
+    pub fn advance(
+        &self,
+        from: &[AcknowledgementState],
+        state: AcknowledgementState,
+    ) -> Option<AcknowledgementState> {
+        let mut current = self.state.lock().unwrap();
+        if !from.contains(&current) {
+            return None;
+        }
+
+        let previous = *current;
+        *current = state;
+        Some(previous)
+    }
+
+    pub fn restore(&self, state: AcknowledgementState) {
+        *self.state.lock().unwrap() = state;
+    }
+}
+
+/// Sends the responses returned by handlers, see
+/// [`crate::CommandHandler::responder`]
+#[async_trait]
+pub trait Responder: Send + Sync {
+    async fn respond(
+        &self,
+        interaction: &InteractionCreate,
+        response: &InteractionResponse,
+    ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>>;
+
+    /// Replaces the message of the initial response, used for the response
+    /// of an interaction that was deferred
+    async fn edit_original(
+        &self,
+        interaction: &InteractionCreate,
+        data: &InteractionResponseData,
+    ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>>;
+}
+
+#[async_trait]
+impl<R: Responder + ?Sized> Responder for Arc<R> {
+    async fn respond(
+        &self,
+        interaction: &InteractionCreate,
+        response: &InteractionResponse,
+    ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+        (**self).respond(interaction, response).await
+    }
+
+    async fn edit_original(
+        &self,
+        interaction: &InteractionCreate,
+        data: &InteractionResponseData,
+    ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+        (**self).edit_original(interaction, data).await
+    }
+}
+
+#[async_trait]
+impl<R: Responder> Responder for Dep<R> {
+    async fn respond(
+        &self,
+        interaction: &InteractionCreate,
+        response: &InteractionResponse,
+    ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+        self.deref().respond(interaction, response).await
+    }
+
+    async fn edit_original(
+        &self,
+        interaction: &InteractionCreate,
+        data: &InteractionResponseData,
+    ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+        self.deref().edit_original(interaction, data).await
+    }
+}
+
+/// Sends a response the way the state of the interaction allows it, a message
+/// for an interaction that was deferred replaces the loading message and any
+/// other response fails with [`Error::AlreadyAcknowledged`]
+pub(crate) async fn send(
+    responder: &dyn Responder,
+    interaction: &InteractionCreate,
+    acknowledgement: &Acknowledgement,
+    mut response: InteractionResponse,
+) -> Result<(), Error> {
+    if acknowledgement.is_ephemeral() {
+        set_ephemeral(&mut response);
+    }
+
+    let state = acknowledges_as(&response);
+    if is_message(&response) {
+        if let Some(previous) = acknowledgement.advance(&[AcknowledgementState::Deferred], state) {
+            let data = response.data.unwrap_or_default();
+            let result = responder.edit_original(interaction, &data).await;
+            return finish(acknowledgement, previous, result);
+        }
+    }
+
+    let Some(previous) = acknowledgement.advance(&[AcknowledgementState::Pending], state) else {
+        return Err(Error::AlreadyAcknowledged);
+    };
+
+    let result = responder.respond(interaction, &response).await;
+    finish(acknowledgement, previous, result)
+}
+
+fn finish(
+    acknowledgement: &Acknowledgement,
+    previous: AcknowledgementState,
+    result: Result<(), Box<dyn ErrorTrait + Send + Sync>>,
+) -> Result<(), Error> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            acknowledgement.restore(previous);
+            Err(Error::ResponseFailed { error: e })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<(&'static str, Option<MessageFlags>)>>,
+        fail: bool,
+    }
+
+    impl Recorder {
+        fn failing() -> Self {
+            Self {
+                fail: true,
+                ..Default::default()
+            }
+        }
+
+        fn record(
+            &self,
+            call: &'static str,
+            flags: Option<MessageFlags>,
+        ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+            self.calls.lock().unwrap().push((call, flags));
+            if self.fail {
+                return Err("sending failed".into());
+            }
+            Ok(())
+        }
+
+        fn calls(&self) -> Vec<(&'static str, Option<MessageFlags>)> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Responder for Recorder {
+        async fn respond(
+            &self,
+            _: &InteractionCreate,
+            response: &InteractionResponse,
+        ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+            self.record("respond", response.data.as_ref().and_then(|d| d.flags))
+        }
+
+        async fn edit_original(
+            &self,
+            _: &InteractionCreate,
+            data: &InteractionResponseData,
+        ) -> Result<(), Box<dyn ErrorTrait + Send + Sync>> {
+            self.record("edit_original", data.flags)
+        }
+    }
+
+    fn interaction() -> InteractionCreate {
+        let interaction = serde_json::json!({
+            "id": "1",
+            "application_id": "2",
+            "type": 1,
+            "token": "token",
+            "version": 1,
+            "channel_id": "3",
+            "locale": "en-US",
+            "entitlements": [],
+            "authorizing_integration_owners": {},
+            "user": {"id": "4", "username": "user", "discriminator": "0", "avatar": null},
+        });
+        InteractionCreate(serde_json::from_value(interaction).unwrap())
+    }
+
+    fn response<R: IntoResponse>(response: R) -> InteractionResponse {
+        response.into_response().unwrap()
+    }
+
+    #[tokio::test]
+    async fn reply_after_defer_edits_original() {
+        let responder = Recorder::default();
+        let interaction = interaction();
+        let ack = Acknowledgement::default();
+
+        send(&responder, &interaction, &ack, response(Defer::new()))
+            .await
+            .unwrap();
+        assert_eq!(ack.state(), AcknowledgementState::Deferred);
+
+        send(&responder, &interaction, &ack, response("done"))
+            .await
+            .unwrap();
+        assert_eq!(ack.state(), AcknowledgementState::Responded);
+        assert_eq!(
+            responder.calls(),
+            vec![("respond", None), ("edit_original", None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn modal_after_defer_is_rejected() {
+        let responder = Recorder::default();
+        let interaction = interaction();
+        let ack = Acknowledgement::default();
+
+        send(&responder, &interaction, &ack, response(Defer::new()))
+            .await
+            .unwrap();
+        let modal = InteractionResponse {
+            kind: InteractionResponseType::Modal,
+            data: Some(
+                InteractionResponseDataBuilder::new()
+                    .custom_id("feedback")
+                    .title("Feedback")
+                    .build(),
+            ),
+        };
+        let result = send(&responder, &interaction, &ack, modal).await;
+
+        assert!(matches!(result, Err(Error::AlreadyAcknowledged)));
+        assert_eq!(ack.state(), AcknowledgementState::Deferred);
+        assert_eq!(responder.calls(), vec![("respond", None)]);
+    }
+
+    #[tokio::test]
+    async fn second_reply_is_rejected() {
+        let responder = Recorder::default();
+        let interaction = interaction();
+        let ack = Acknowledgement::default();
+
+        send(&responder, &interaction, &ack, response("first"))
+            .await
+            .unwrap();
+        let result = send(&responder, &interaction, &ack, response("second")).await;
+
+        assert!(matches!(result, Err(Error::AlreadyAcknowledged)));
+        assert_eq!(responder.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn second_defer_is_rejected() {
+        let responder = Recorder::default();
+        let interaction = interaction();
+        let ack = Acknowledgement::default();
+
+        send(&responder, &interaction, &ack, response(Defer::new()))
+            .await
+            .unwrap();
+        let result = send(&responder, &interaction, &ack, response(Defer::new())).await;
+
+        assert!(matches!(result, Err(Error::AlreadyAcknowledged)));
+        assert_eq!(ack.state(), AcknowledgementState::Deferred);
+    }
+
+    #[tokio::test]
+    async fn failed_send_restores_state() {
+        let responder = Recorder::failing();
+        let interaction = interaction();
+        let ack = Acknowledgement::default();
+
+        let result = send(&responder, &interaction, &ack, response("reply")).await;
+        assert!(matches!(result, Err(Error::ResponseFailed { .. })));
+        assert_eq!(ack.state(), AcknowledgementState::Pending);
+
+        ack.restore(AcknowledgementState::Deferred);
+        let result = send(&responder, &interaction, &ack, response("reply")).await;
+        assert!(matches!(result, Err(Error::ResponseFailed { .. })));
+        assert_eq!(ack.state(), AcknowledgementState::Deferred);
+    }
+
+    #[tokio::test]
+    async fn ephemeral_acknowledgement_flags_messages() {
+        let responder = Recorder::default();
+        let interaction = interaction();
+
+        let ack = Acknowledgement::new(true);
+        send(&responder, &interaction, &ack, response(Defer::new()))
+            .await
+            .unwrap();
+        send(&responder, &interaction, &ack, response("done"))
+            .await
+            .unwrap();
+
+        let ack = Acknowledgement::new(true);
+        send(&responder, &interaction, &ack, response("reply"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            responder.calls(),
+            vec![
+                ("respond", Some(MessageFlags::EPHEMERAL)),
+                ("edit_original", Some(MessageFlags::EPHEMERAL)),
+                ("respond", Some(MessageFlags::EPHEMERAL)),
+            ]
+        );
+    }
+}
@@ -1,5 +1,10 @@
+use crate::response::Responder;
+use async_trait::async_trait;
 use deppy::{Initialize, ServiceCollectionBuilder, ServiceHandler};
+use std::error::Error;
 use twilight_cache_inmemory::InMemoryCache;
+use twilight_model::gateway::payload::incoming::InteractionCreate;
+use twilight_model::http::interaction::{InteractionResponse, InteractionResponseData};
 
 #[derive(Clone)]
 struct InitializeHttp {
@@ -36,3 +41,49 @@ impl AddTwilightServices for ServiceCollectionBuilder {
         self.add_service(deppy::ServiceType::Singleton, InitializeTwilight)
     }
 }
+
+/// Responds through the interaction endpoints with the application id of the
+/// interaction
+#[async_trait]
+impl Responder for twilight_http::Client {
+    async fn respond(
+        &self,
+        interaction: &InteractionCreate,
+        response: &InteractionResponse,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.interaction(interaction.application_id)
+            .create_response(interaction.id, &interaction.token, response)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn edit_original(
+        &self,
+        interaction: &InteractionCreate,
+        data: &InteractionResponseData,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let client = self.interaction(interaction.application_id);
+
+        // Fields that aren't set are left unchanged instead of being removed
+        let mut request = client.update_response(&interaction.token);
+        if let Some(content) = &data.content {
+            request = request.content(Some(content))?;
+        }
+        if let Some(embeds) = &data.embeds {
+            request = request.embeds(Some(embeds))?;
+        }
+        if let Some(components) = &data.components {
+            request = request.components(Some(components))?;
+        }
+        if let Some(allowed_mentions) = &data.allowed_mentions {
+            request = request.allowed_mentions(Some(allowed_mentions));
+        }
+        if let Some(attachments) = &data.attachments {
+            request = request.attachments(attachments)?;
+        }
+
+        request.await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::response::Acknowledgement;
+use crate::{FromOption, Inject};
+use deppy::ServiceHandler;
+use twilight_model::application::command::{Command, CommandOption, CommandType};
+use twilight_model::application::interaction::application_command::{
+    CommandData, CommandDataOption, CommandOptionValue,
+};
+use twilight_model::gateway::payload::incoming::InteractionCreate;
+use twilight_model::guild::Permissions;
+use twilight_util::builder::command::CommandBuilder;
+
+/// A chat input command that is handled by a function instead of a
+/// controller, see [`crate::CommandHandler::command`]
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    name: String,
+    description: String,
+    options: Vec<CommandOption>,
+    default_member_permissions: Option<Permissions>,
+    dm_permission: Option<bool>,
+    nsfw: Option<bool>,
+    auto_defer: Option<bool>,
+    ephemeral: bool,
+}
+
+impl CommandSpec {
+    pub fn new(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            options: vec![],
+            default_member_permissions: None,
+            dm_permission: None,
+            nsfw: None,
+            auto_defer: None,
+            ephemeral: false,
+        }
+    }
+
+    /// Adds an option, sub commands and groups can be added as options too
+    pub fn option(mut self, option: impl Into<CommandOption>) -> Self {
+        self.options.push(option.into());
+        self
+    }
+
+    pub fn default_member_permissions(mut self, permissions: Permissions) -> Self {
+        self.default_member_permissions = Some(permissions);
+        self
+    }
+
+    pub fn dm_permission(mut self, dm_permission: bool) -> Self {
+        self.dm_permission = Some(dm_permission);
+        self
+    }
+
+    pub fn nsfw(mut self, nsfw: bool) -> Self {
+        self.nsfw = Some(nsfw);
+        self
+    }
+
+    /// Overrides [`crate::CommandHandler::auto_defer`] for this command
+    pub fn auto_defer(mut self, auto_defer: bool) -> Self {
+        self.auto_defer = Some(auto_defer);
+        self
+    }
+
+    /// Makes every message the framework sends for this command ephemeral
+    pub fn ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    pub(crate) fn get_auto_defer(&self) -> Option<bool> {
+        self.auto_defer
+    }
+
+    pub(crate) fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    pub(crate) fn build(self) -> Command {
+        let mut builder = self.options.into_iter().fold(
+            CommandBuilder::new(self.name, self.description, CommandType::ChatInput),
+            |builder, option| builder.option(option),
+        );
+        if let Some(permissions) = self.default_member_permissions {
+            builder = builder.default_member_permissions(permissions);
+        }
+        if let Some(dm_permission) = self.dm_permission {
+            builder = builder.dm_permission(dm_permission);
+        }
+        if let Some(nsfw) = self.nsfw {
+            builder = builder.nsfw(nsfw);
+        }
+
+        builder.build()
+    }
+}
+
+/// What a command function receives, `services` is a new scope of the
+/// service handler
+#[derive(Debug)]
+pub struct CommandContext<S> {
+    pub interaction: InteractionCreate,
+    pub data: CommandData,
+    pub services: S,
+    pub(crate) acknowledgement: Acknowledgement,
+}
+
+impl<S> CommandContext<S> {
+    /// The options of the invoked sub command, or of the command itself if it
+    /// has no sub commands
+    pub fn options(&self) -> &[CommandDataOption] {
+        let mut options = self.data.options.as_slice();
+        while let Some(CommandDataOption {
+            value: CommandOptionValue::SubCommand(o) | CommandOptionValue::SubCommandGroup(o),
+            ..
+        }) = options.first()
+        {
+            options = o;
+        }
+
+        options
+    }
+
+    /// Converts the option with the given name, `None` if it is missing or
+    /// has a different type
+    pub fn option<T: FromOption>(&self, name: &str) -> Option<T> {
+        let option = self.options().iter().find(|o| o.name == name)?;
+
+        T::from_option(option.value.clone(), &self.data)
+    }
+
+    /// Gets a service from the scope the same way `#[inject]` parameters do
+    pub fn inject<T: Inject<S>>(&self) -> Option<T>
+    where
+        S: ServiceHandler,
+    {
+        T::inject(&self.services)
+    }
+
+    /// The response state of the interaction, shared with the framework that
+    /// sends the value returned by the command
+    pub fn acknowledgement(&self) -> &Acknowledgement {
+        &self.acknowledgement
+    }
+
+    /// The response helpers for the interaction, `None` if the HTTP client
+    /// isn't a service
+    #[cfg(feature = "services")]
+    pub fn context(&self) -> Option<crate::Context>
+    where
+        S: ServiceHandler,
+    {
+        crate::Context::new(&self.interaction, &self.services, &self.acknowledgement)
+    }
+}